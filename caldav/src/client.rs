@@ -0,0 +1,192 @@
+use std::io::BufReader;
+
+use chrono::{DateTime, Utc};
+use ics_parser::{EventSink, IcsParser};
+
+use crate::{
+    error::CalDavError,
+    multistatus::{parse_multistatus, Resource},
+    transport::{Request, Transport},
+};
+
+// A calendar collection found by `CalDavClient::calendars`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Calendar {
+    pub url: String,
+    pub name: Option<String>,
+    // e.g. `#1BADF8FF`
+    pub color: Option<String>,
+}
+
+// Reads calendars from a CalDAV server (RFC 4791), e.g. Nextcloud, Radicale or Fastmail.
+// Only the events in a time range are downloaded, not the whole calendar.
+pub struct CalDavClient<T> {
+    transport: T,
+    authorization: Option<String>,
+}
+
+impl<T: Transport> CalDavClient<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            authorization: None,
+        }
+    }
+
+    // HTTP basic authentication, most servers take an app password here
+    pub fn basic_auth(mut self, user: &str, password: &str) -> Self {
+        let credentials = format!("{}:{}", user, password);
+        self.authorization = Some(format!("Basic {}", base64(credentials.as_bytes())));
+        self
+    }
+
+    // The calendars in a collection such as the calendar home `/calendars/user/`,
+    // or the calendar itself when `url` is one
+    pub fn calendars(&mut self, url: &str) -> Result<Vec<Calendar>, CalDavError> {
+        let body = r#"<?xml version="1.0" encoding="utf-8"?>
+<D:propfind xmlns:D="DAV:" xmlns:A="http://apple.com/ns/ical/">
+  <D:prop>
+    <D:resourcetype/>
+    <D:displayname/>
+    <A:calendar-color/>
+  </D:prop>
+</D:propfind>
+"#;
+        let resources = self.multistatus("PROPFIND", url, body.to_string(), |_| Ok(()))?;
+
+        Ok(resources
+            .into_iter()
+            .filter(|resource| resource.is_calendar)
+            .map(|resource| Calendar {
+                url: resolve_href(url, &resource.href),
+                name: resource.display_name,
+                color: resource.color,
+            })
+            .collect())
+    }
+
+    // Passes the events and tasks of the calendar at `url` that overlap `start..end` to
+    // `parser`, which should use the same window. Returns the number of calendar objects.
+    pub fn query<S: EventSink>(
+        &mut self,
+        url: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        parser: &mut IcsParser<S>,
+    ) -> Result<usize, CalDavError> {
+        // Filters of several components must all match, so tasks take a query of their own
+        let mut count = 0;
+        for component in ["VEVENT", "VTODO"] {
+            let body = format!(
+                r#"<?xml version="1.0" encoding="utf-8"?>
+<C:calendar-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <D:prop>
+    <D:getetag/>
+    <C:calendar-data/>
+  </D:prop>
+  <C:filter>
+    <C:comp-filter name="VCALENDAR">
+      <C:comp-filter name="{}">
+        <C:time-range start="{}" end="{}"/>
+      </C:comp-filter>
+    </C:comp-filter>
+  </C:filter>
+</C:calendar-query>
+"#,
+                component,
+                format_utc(&start),
+                format_utc(&end)
+            );
+            // Each object is a VCALENDAR of its own, one after the other is a valid feed
+            let resources = self.multistatus("REPORT", url, body, |data| {
+                parser.parse_ics_chunk(data)?;
+                Ok(())
+            })?;
+            count += resources
+                .iter()
+                .filter(|resource| resource.has_calendar_data)
+                .count();
+        }
+        Ok(count)
+    }
+
+    // PROPFIND and REPORT both answer with 207 Multi-Status
+    fn multistatus(
+        &mut self,
+        method: &'static str,
+        url: &str,
+        body: String,
+        calendar_data: impl FnMut(&str) -> Result<(), CalDavError>,
+    ) -> Result<Vec<Resource>, CalDavError> {
+        let mut headers = vec![
+            ("Depth", "1".to_string()),
+            ("Content-Type", "application/xml; charset=utf-8".to_string()),
+        ];
+        if let Some(authorization) = &self.authorization {
+            headers.push(("Authorization", authorization.clone()));
+        }
+        let request = Request {
+            method,
+            url: url.to_string(),
+            headers,
+            body,
+        };
+
+        let response = self.transport.send(&request)?;
+        if response.status != 207 {
+            return Err(CalDavError::Status(response.status));
+        }
+        parse_multistatus(BufReader::new(response.body), calendar_data)
+    }
+}
+
+fn format_utc(datetime: &DateTime<Utc>) -> String {
+    datetime.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// Hrefs are usually absolute paths on the same server
+fn resolve_href(base: &str, href: &str) -> String {
+    if href.contains("://") {
+        return href.to_string();
+    }
+    let origin_end = base
+        .find("://")
+        .and_then(|scheme_end| {
+            let authority = scheme_end + 3;
+            base[authority..].find('/').map(|path| authority + path)
+        })
+        .unwrap_or(base.len());
+    if href.starts_with('/') {
+        format!("{}{}", &base[..origin_end], href)
+    } else {
+        // Relative to the collection
+        let directory_end = base.rfind('/').filter(|end| *end >= origin_end);
+        match directory_end {
+            Some(end) => format!("{}{}", &base[..=end], href),
+            None => format!("{}/{}", base, href),
+        }
+    }
+}
+
+// RFC 4648 with padding, for the Authorization header
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0_u32, |group, (index, &byte)| {
+                group | u32::from(byte) << (16 - index * 8)
+            });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - index * 6)) as usize & 0x3f] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
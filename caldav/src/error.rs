@@ -0,0 +1,49 @@
+use std::{error::Error, fmt, io};
+
+use ics_parser::ParseError;
+
+// Why a CalDAV request failed
+#[derive(Debug)]
+pub enum CalDavError {
+    // The request could not be sent or the response not read
+    Transport(io::Error),
+    // Anything but 207 Multi-Status, e.g. 401 for wrong credentials
+    Status(u16),
+    // The response is not a multistatus document
+    InvalidXml(String),
+    // A strict parser failed on the calendar data
+    Parse(ParseError),
+}
+
+impl fmt::Display for CalDavError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalDavError::Transport(error) => write!(f, "request failed, {}", error),
+            CalDavError::Status(status) => write!(f, "unexpected response code {}", status),
+            CalDavError::InvalidXml(error) => write!(f, "invalid multistatus, {}", error),
+            CalDavError::Parse(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for CalDavError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CalDavError::Transport(error) => Some(error),
+            CalDavError::Parse(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CalDavError {
+    fn from(error: io::Error) -> Self {
+        CalDavError::Transport(error)
+    }
+}
+
+impl From<ParseError> for CalDavError {
+    fn from(error: ParseError) -> Self {
+        CalDavError::Parse(error)
+    }
+}
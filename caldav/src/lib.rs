@@ -0,0 +1,8 @@
+pub use client::{CalDavClient, Calendar};
+pub use error::CalDavError;
+pub use transport::{Request, Response, TcpTransport, Transport};
+
+mod client;
+mod error;
+mod multistatus;
+mod transport;
@@ -0,0 +1,206 @@
+use std::io::BufRead;
+
+use quick_xml::{
+    events::Event,
+    name::{Namespace, ResolveResult},
+    NsReader,
+};
+
+use crate::error::CalDavError;
+
+const DAV: &[u8] = b"DAV:";
+const CALDAV: &[u8] = b"urn:ietf:params:xml:ns:caldav";
+const APPLE_ICAL: &[u8] = b"http://apple.com/ns/ical/";
+
+// A `<response>` of a 207 Multi-Status, with the properties the server could return
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Resource {
+    pub href: String,
+    pub display_name: Option<String>,
+    // e.g. `#1BADF8FF`
+    pub color: Option<String>,
+    // The resource type includes `calendar`
+    pub is_calendar: bool,
+    pub etag: Option<String>,
+    // The calendar data was passed on while reading
+    pub has_calendar_data: bool,
+}
+
+// The namespace of an element, servers use different prefixes for the same one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ns {
+    Dav,
+    CalDav,
+    AppleIcal,
+    Other,
+}
+
+// RFC 4918 13: `<multistatus>` holds a `<response>` per resource, and each `<response>` a
+// `<propstat>` per status. Only properties with a 2xx status are kept.
+//
+// Calendar data is handed to `calendar_data` piece by piece as it is read, ending with a
+// line break, so only the object being read is held in memory.
+pub fn parse_multistatus(
+    reader: impl BufRead,
+    mut calendar_data: impl FnMut(&str) -> Result<(), CalDavError>,
+) -> Result<Vec<Resource>, CalDavError> {
+    let mut reader = NsReader::from_reader(reader);
+    let mut buf = Vec::new();
+    let mut resources = Vec::new();
+    let mut resource = Resource::default();
+    // Properties of the current `<propstat>`, until its status is known
+    let mut properties = Resource::default();
+    let mut properties_ok = true;
+    let mut path: Vec<(Ns, Vec<u8>)> = Vec::new();
+    let mut text = String::new();
+    // Inside `<calendar-data>`, whitespace at the end of a piece is held back until more
+    // data follows, a line starting with whitespace would continue the previous one
+    let mut data: Option<CalendarData> = None;
+
+    loop {
+        buf.clear();
+        let (namespace, event) = reader
+            .read_resolved_event_into(&mut buf)
+            .map_err(xml_error)?;
+        let ended = match event {
+            Event::Start(start) => {
+                path.push(name(&namespace, start.local_name().into_inner()));
+                if let [.., (Ns::Dav, parent), (Ns::CalDav, element)] = path.as_slice() {
+                    if parent == b"prop" && element == b"calendar-data" {
+                        data = Some(CalendarData::default());
+                    }
+                }
+                text.clear();
+                None
+            }
+            Event::Empty(empty) => {
+                path.push(name(&namespace, empty.local_name().into_inner()));
+                text.clear();
+                path.pop()
+            }
+            Event::End(_) => path.pop(),
+            Event::Text(content) => {
+                let content = content.unescape().map_err(xml_error)?;
+                match data.as_mut() {
+                    Some(data) => data.push(&content, &mut calendar_data)?,
+                    None => text.push_str(&content),
+                }
+                None
+            }
+            Event::CData(content) => {
+                let content = content.decode().map_err(|error| xml_error(error.into()))?;
+                match data.as_mut() {
+                    Some(data) => data.push(&content, &mut calendar_data)?,
+                    None => text.push_str(&content),
+                }
+                None
+            }
+            Event::Eof => break,
+            _ => None,
+        };
+        let Some((namespace, local_name)) = ended else {
+            continue;
+        };
+        let parent = path
+            .last()
+            .map(|(namespace, local_name)| (*namespace, local_name.as_slice()));
+
+        let value = || Some(text.trim().to_string()).filter(|value| !value.is_empty());
+        match (parent, (namespace, local_name.as_slice())) {
+            (Some((Ns::Dav, b"response")), (Ns::Dav, b"href")) => {
+                resource.href = text.trim().to_string()
+            }
+            (Some((Ns::Dav, b"propstat")), (Ns::Dav, b"status")) => {
+                // e.g. `HTTP/1.1 404 Not Found`
+                properties_ok = text
+                    .split_whitespace()
+                    .nth(1)
+                    .is_some_and(|status| status.len() == 3 && status.starts_with('2'));
+            }
+            (Some((Ns::Dav, b"prop")), (Ns::Dav, b"displayname")) => {
+                properties.display_name = value()
+            }
+            (Some((Ns::Dav, b"prop")), (Ns::Dav, b"getetag")) => properties.etag = value(),
+            (Some((Ns::Dav, b"prop")), (Ns::AppleIcal, b"calendar-color")) => {
+                properties.color = value()
+            }
+            (Some((Ns::Dav, b"prop")), (Ns::CalDav, b"calendar-data")) => {
+                if let Some(data) = data.take() {
+                    if data.started {
+                        calendar_data("\r\n")?;
+                        properties.has_calendar_data = true;
+                    }
+                }
+            }
+            (Some((Ns::Dav, b"resourcetype")), (Ns::CalDav, b"calendar")) => {
+                properties.is_calendar = true
+            }
+            (_, (Ns::Dav, b"propstat")) => {
+                let properties = std::mem::take(&mut properties);
+                if std::mem::replace(&mut properties_ok, true) {
+                    resource.display_name = resource.display_name.or(properties.display_name);
+                    resource.color = resource.color.or(properties.color);
+                    resource.is_calendar |= properties.is_calendar;
+                    resource.etag = resource.etag.or(properties.etag);
+                    resource.has_calendar_data |= properties.has_calendar_data;
+                }
+            }
+            (_, (Ns::Dav, b"response")) => resources.push(std::mem::take(&mut resource)),
+            _ => {}
+        }
+    }
+
+    Ok(resources)
+}
+
+// The text of a `<calendar-data>` element, trimmed as it is passed on
+#[derive(Default)]
+struct CalendarData {
+    started: bool,
+    whitespace: String,
+}
+
+impl CalendarData {
+    fn push(
+        &mut self,
+        content: &str,
+        calendar_data: &mut impl FnMut(&str) -> Result<(), CalDavError>,
+    ) -> Result<(), CalDavError> {
+        let content = if self.started {
+            content
+        } else {
+            content.trim_start()
+        };
+        let trimmed = content.trim_end();
+        if !trimmed.is_empty() {
+            if !self.whitespace.is_empty() {
+                calendar_data(&self.whitespace)?;
+                self.whitespace.clear();
+            }
+            calendar_data(trimmed)?;
+            self.started = true;
+        }
+        self.whitespace.push_str(&content[trimmed.len()..]);
+        Ok(())
+    }
+}
+
+// A connection that fails while reading is not a problem of the document
+fn xml_error(error: quick_xml::Error) -> CalDavError {
+    match error {
+        quick_xml::Error::Io(error) => {
+            CalDavError::Transport(std::io::Error::new(error.kind(), error.to_string()))
+        }
+        error => CalDavError::InvalidXml(error.to_string()),
+    }
+}
+
+fn name(namespace: &ResolveResult, local_name: &[u8]) -> (Ns, Vec<u8>) {
+    let namespace = match namespace {
+        ResolveResult::Bound(Namespace(DAV)) => Ns::Dav,
+        ResolveResult::Bound(Namespace(CALDAV)) => Ns::CalDav,
+        ResolveResult::Bound(Namespace(APPLE_ICAL)) => Ns::AppleIcal,
+        _ => Ns::Other,
+    };
+    (namespace, local_name.to_vec())
+}
@@ -0,0 +1,228 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+// An HTTP request, CalDAV needs the PROPFIND and REPORT methods
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: &'static str,
+    pub url: String,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: String,
+}
+
+// The body is read while it is parsed, a report can be larger than the heap of the device
+pub struct Response<'a> {
+    pub status: u16,
+    pub body: Box<dyn Read + 'a>,
+}
+
+// Sends the requests of a `CalDavClient`, so the same client works with the HTTP client
+// of the device and with `TcpTransport` on a computer
+pub trait Transport {
+    fn send(&mut self, request: &Request) -> io::Result<Response<'_>>;
+}
+
+impl<T: Transport + ?Sized> Transport for &mut T {
+    fn send(&mut self, request: &Request) -> io::Result<Response<'_>> {
+        (**self).send(request)
+    }
+}
+
+// Plain HTTP over a new connection per request, for servers on the local network.
+// HTTPS needs a transport of its own.
+#[derive(Debug, Clone, Default)]
+pub struct TcpTransport {
+    timeout: Option<Duration>,
+}
+
+impl TcpTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // For connecting, reading and writing each
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+impl Transport for TcpTransport {
+    fn send(&mut self, request: &Request) -> io::Result<Response<'_>> {
+        let (authority, path) = split_url(&request.url)?;
+        // The port follows the last colon, except in an IPv6 address like `[::1]`
+        let has_port = authority
+            .rsplit(']')
+            .next()
+            .is_some_and(|host| host.contains(':'));
+        let address = if has_port {
+            authority.to_string()
+        } else {
+            format!("{}:80", authority)
+        };
+        let mut stream = match self.timeout {
+            Some(timeout) => {
+                let address = std::net::ToSocketAddrs::to_socket_addrs(&address)?
+                    .next()
+                    .ok_or_else(|| invalid_input(&request.url))?;
+                TcpStream::connect_timeout(&address, timeout)?
+            }
+            None => TcpStream::connect(&address)?,
+        };
+        stream.set_read_timeout(self.timeout)?;
+        stream.set_write_timeout(self.timeout)?;
+
+        let mut head = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+            request.method,
+            path,
+            authority,
+            request.body.len()
+        );
+        for (name, value) in &request.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(request.body.as_bytes())?;
+
+        let mut reader = BufReader::new(stream);
+        let head = read_head(&mut reader)?;
+        // The server closes the connection after the response
+        let body: Box<dyn Read> = match head.body_length {
+            BodyLength::Chunked => Box::new(ChunkedReader {
+                inner: reader,
+                remaining: 0,
+                done: false,
+            }),
+            BodyLength::Length(length) => Box::new(reader.take(length)),
+            BodyLength::UntilClose => Box::new(reader),
+        };
+        Ok(Response {
+            status: head.status,
+            body,
+        })
+    }
+}
+
+// `http://localhost:5232/user/calendar/` is `localhost:5232` and `/user/calendar/`
+fn split_url(url: &str) -> io::Result<(&str, &str)> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| invalid_input(url))?;
+    Ok(match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    })
+}
+
+struct Head {
+    status: u16,
+    body_length: BodyLength,
+}
+
+enum BodyLength {
+    Chunked,
+    Length(u64),
+    UntilClose,
+}
+
+// The status line and headers, up to the empty line before the body
+fn read_head(reader: &mut impl BufRead) -> io::Result<Head> {
+    let mut lines = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(malformed());
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            break;
+        }
+        lines.push(line.to_string());
+    }
+
+    // e.g. `HTTP/1.1 207 Multi-Status`
+    let status = lines
+        .first()
+        .and_then(|line| line.split(' ').nth(1))
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(malformed)?;
+    let header = |name: &str| {
+        lines.iter().skip(1).find_map(|line| {
+            let (header, value) = line.split_once(':')?;
+            header
+                .trim()
+                .eq_ignore_ascii_case(name)
+                .then(|| value.trim())
+        })
+    };
+
+    let body_length =
+        if header("Transfer-Encoding").is_some_and(|value| value.eq_ignore_ascii_case("chunked")) {
+            BodyLength::Chunked
+        } else if let Some(length) = header("Content-Length") {
+            BodyLength::Length(length.parse().map_err(|_| malformed())?)
+        } else {
+            BodyLength::UntilClose
+        };
+    Ok(Head {
+        status,
+        body_length,
+    })
+}
+
+// Each chunk is its size in hex and the data, up to a chunk of size 0
+struct ChunkedReader<R> {
+    inner: R,
+    // Of the current chunk
+    remaining: usize,
+    done: bool,
+}
+
+impl<R: BufRead> Read for ChunkedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.done || buf.is_empty() {
+            return Ok(0);
+        }
+        if self.remaining == 0 {
+            let mut line = String::new();
+            self.inner.read_line(&mut line)?;
+            // Chunk extensions follow a semicolon
+            let size = line.split(';').next().unwrap_or_default().trim();
+            let size = usize::from_str_radix(size, 16).map_err(|_| malformed())?;
+            if size == 0 {
+                self.done = true;
+                return Ok(0);
+            }
+            self.remaining = size;
+        }
+
+        let length = buf.len().min(self.remaining);
+        let read = self.inner.read(&mut buf[..length])?;
+        if read == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.remaining -= read;
+        if self.remaining == 0 {
+            // The line break after the data
+            let mut line_break = [0; 2];
+            self.inner.read_exact(&mut line_break)?;
+        }
+        Ok(read)
+    }
+}
+
+fn malformed() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed HTTP response")
+}
+
+fn invalid_input(url: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("not an http:// URL: {}", url),
+    )
+}
@@ -0,0 +1,411 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use caldav::{CalDavClient, CalDavError, Calendar, TcpTransport};
+use chrono::{TimeZone, Utc};
+use ics_parser::{EventTime, IcsParser};
+
+// `alice:secret`
+const AUTHORIZATION: &str = "Basic YWxpY2U6c2VjcmV0";
+
+const HOME: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<multistatus xmlns="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav" xmlns:ICAL="http://apple.com/ns/ical/">
+  <response>
+    <href>/dav/calendars/alice/</href>
+    <propstat>
+      <prop><resourcetype><collection/></resourcetype></prop>
+      <status>HTTP/1.1 200 OK</status>
+    </propstat>
+    <propstat>
+      <prop><displayname/><ICAL:calendar-color/></prop>
+      <status>HTTP/1.1 404 Not Found</status>
+    </propstat>
+  </response>
+  <response>
+    <href>/dav/calendars/alice/work/</href>
+    <propstat>
+      <prop>
+        <resourcetype><collection/><C:calendar/></resourcetype>
+        <displayname>Work &amp; school</displayname>
+        <ICAL:calendar-color>#1BADF8FF</ICAL:calendar-color>
+      </prop>
+      <status>HTTP/1.1 200 OK</status>
+    </propstat>
+  </response>
+  <response>
+    <href>personal/</href>
+    <propstat>
+      <prop><resourcetype><collection/><C:calendar/></resourcetype></prop>
+      <status>HTTP/1.1 200 OK</status>
+    </propstat>
+  </response>
+  <response>
+    <href>/dav/calendars/alice/contacts/</href>
+    <propstat>
+      <prop>
+        <resourcetype><collection/><CR:addressbook xmlns:CR="urn:ietf:params:xml:ns:carddav"/></resourcetype>
+        <displayname>Contacts</displayname>
+      </prop>
+      <status>HTTP/1.1 200 OK</status>
+    </propstat>
+  </response>
+</multistatus>
+"#;
+
+// Calendar objects of the work calendar, a weekly meeting, a single event, one far
+// in the future and a task
+const STANDUP: &str = "BEGIN:VCALENDAR\r\n\
+    VERSION:2.0\r\n\
+    BEGIN:VEVENT\r\n\
+    UID:standup@example.com\r\n\
+    DTSTART:20241104T013000Z\r\n\
+    DTEND:20241104T020000Z\r\n\
+    RRULE:FREQ=WEEKLY;BYDAY=MO\r\n\
+    SUMMARY:Standup\r\n\
+    END:VEVENT\r\n\
+    END:VCALENDAR";
+const REVIEW: &str = "BEGIN:VCALENDAR\r\n\
+    VERSION:2.0\r\n\
+    BEGIN:VEVENT\r\n\
+    UID:review@example.com\r\n\
+    DTSTART:20241210T060000Z\r\n\
+    DTEND:20241210T070000Z\r\n\
+    SUMMARY:Review <draft> & notes\r\n\
+    END:VEVENT\r\n\
+    END:VCALENDAR\r\n";
+const RETREAT: &str = "BEGIN:VCALENDAR\r\n\
+    VERSION:2.0\r\n\
+    BEGIN:VEVENT\r\n\
+    UID:retreat@example.com\r\n\
+    DTSTART;VALUE=DATE:20250301\r\n\
+    SUMMARY:Retreat\r\n\
+    END:VEVENT\r\n\
+    END:VCALENDAR\r\n";
+const REPORT: &str = "BEGIN:VCALENDAR\r\n\
+    VERSION:2.0\r\n\
+    BEGIN:VTODO\r\n\
+    UID:report@example.com\r\n\
+    DUE:20241220T090000Z\r\n\
+    SUMMARY:Hand in the report\r\n\
+    END:VTODO\r\n\
+    END:VCALENDAR\r\n";
+
+#[derive(Debug, Clone)]
+struct Recorded {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Recorded {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// A stand-in for Radicale on a free local port, it answers like the real server but
+// keeps its calendars in memory
+struct Server {
+    url: String,
+    requests: Arc<Mutex<Vec<Recorded>>>,
+}
+
+impl Server {
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let request = read_request(&mut stream);
+                recorded.lock().unwrap().push(request.clone());
+                respond(&mut stream, &request);
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    fn requests(&self) -> Vec<Recorded> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn read_request(stream: &mut TcpStream) -> Recorded {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap().to_string();
+    let path = parts.next().unwrap().to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':').unwrap();
+        headers.push((name.to_string(), value.trim().to_string()));
+    }
+
+    let mut request = Recorded {
+        method,
+        path,
+        headers,
+        body: String::new(),
+    };
+    let length = request.header("Content-Length").unwrap().parse().unwrap();
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).unwrap();
+    request.body = String::from_utf8(body).unwrap();
+    request
+}
+
+fn respond(stream: &mut TcpStream, request: &Recorded) {
+    if request.header("Authorization") != Some(AUTHORIZATION) {
+        return write_response(stream, "401 Unauthorized", "", false);
+    }
+    match (request.method.as_str(), request.path.as_str()) {
+        ("PROPFIND", "/dav/calendars/alice/") => {
+            write_response(stream, "207 Multi-Status", HOME, false)
+        }
+        ("REPORT", "/dav/calendars/alice/work/") => {
+            let body = calendar_query(&request.body);
+            // Large reports come in chunks
+            write_response(stream, "207 Multi-Status", &body, true)
+        }
+        ("REPORT", "/dav/calendars/alice/broken/") => write_response(
+            stream,
+            "207 Multi-Status",
+            r#"<D:multistatus xmlns:D="DAV:"><D:response></D:multistatus>"#,
+            false,
+        ),
+        _ => write_response(stream, "404 Not Found", "", false),
+    }
+}
+
+// Objects of the queried component overlapping the time range of the query, the
+// recurring one always does
+fn calendar_query(query: &str) -> String {
+    let attribute = |name: &str| {
+        let start = query.find(&format!("{}=\"", name)).unwrap() + name.len() + 2;
+        query[start..start + 16].to_string()
+    };
+    let (start, end) = (attribute("start"), attribute("end"));
+    let component = if query.contains(r#"<C:comp-filter name="VTODO">"#) {
+        "VTODO"
+    } else {
+        "VEVENT"
+    };
+
+    let mut body = String::from(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<D:multistatus xmlns:D="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">
+"#,
+    );
+    let objects = [
+        ("standup.ics", STANDUP, "00000000T000000Z"),
+        ("review.ics", REVIEW, "20241210T060000Z"),
+        ("retreat.ics", RETREAT, "20250301T000000Z"),
+        ("report.ics", REPORT, "20241220T090000Z"),
+    ];
+    for (index, (name, data, first)) in objects.into_iter().enumerate() {
+        if !data.contains(&format!("BEGIN:{}", component))
+            || first >= end.as_str()
+            || (index > 0 && first < start.as_str())
+        {
+            continue;
+        }
+        // Radicale escapes the data, other servers use CDATA
+        let data = if index == 0 {
+            format!("<![CDATA[{}]]>", data)
+        } else {
+            data.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('\r', "&#13;")
+        };
+        body.push_str(&format!(
+            r#"  <D:response>
+    <D:href>/dav/calendars/alice/work/{}</D:href>
+    <D:propstat>
+      <D:prop><D:getetag>"{}"</D:getetag><cal:calendar-data>{}</cal:calendar-data></D:prop>
+      <D:status>HTTP/1.1 200 OK</D:status>
+    </D:propstat>
+  </D:response>
+"#,
+            name, index, data
+        ));
+    }
+    // A resource without calendar data
+    body.push_str(
+        r#"  <D:response>
+    <D:href>/dav/calendars/alice/work/missing.ics</D:href>
+    <D:propstat>
+      <D:prop><cal:calendar-data/></D:prop>
+      <D:status>HTTP/1.1 404 Not Found</D:status>
+    </D:propstat>
+  </D:response>
+</D:multistatus>
+"#,
+    );
+    body
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, body: &str, chunked: bool) {
+    let mut response = format!("HTTP/1.1 {}\r\nContent-Type: application/xml\r\n", status);
+    if chunked {
+        response.push_str("Transfer-Encoding: chunked\r\n\r\n");
+        for chunk in body.as_bytes().chunks(100) {
+            response.push_str(&format!("{:x}\r\n", chunk.len()));
+            response.push_str(std::str::from_utf8(chunk).unwrap());
+            response.push_str("\r\n");
+        }
+        response.push_str("0\r\n\r\n");
+    } else {
+        response.push_str(&format!("Content-Length: {}\r\n\r\n{}", body.len(), body));
+    }
+    stream.write_all(response.as_bytes()).unwrap();
+}
+
+fn client() -> CalDavClient<TcpTransport> {
+    CalDavClient::new(TcpTransport::new()).basic_auth("alice", "secret")
+}
+
+#[test]
+fn lists_calendars_in_the_home_set() {
+    let server = Server::start();
+    let home = format!("{}/dav/calendars/alice/", server.url);
+
+    let calendars = client().calendars(&home).unwrap();
+    assert_eq!(
+        calendars,
+        [
+            Calendar {
+                url: format!("{}/dav/calendars/alice/work/", server.url),
+                name: Some("Work & school".to_string()),
+                color: Some("#1BADF8FF".to_string()),
+            },
+            Calendar {
+                url: format!("{}/dav/calendars/alice/personal/", server.url),
+                name: None,
+                color: None,
+            },
+        ]
+    );
+
+    let request = &server.requests()[0];
+    assert_eq!(request.method, "PROPFIND");
+    assert_eq!(request.header("Depth"), Some("1"));
+    assert!(request.body.contains("<D:displayname/>"));
+}
+
+#[test]
+fn queries_events_in_a_time_range() {
+    let server = Server::start();
+    let start = Utc.with_ymd_and_hms(2024, 12, 1, 0, 0, 0).unwrap();
+    let end = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    let mut parser = IcsParser::new(Some(start), Some(end)).strict(true);
+
+    let count = client()
+        .query(
+            &format!("{}/dav/calendars/alice/work/", server.url),
+            start,
+            end,
+            &mut parser,
+        )
+        .unwrap();
+    // The retreat is left out by the server
+    assert_eq!(count, 3);
+
+    let output = parser.finish().unwrap();
+    let mut events = output.events;
+    events.sort_by_key(|event| event.start);
+    let summaries = events
+        .iter()
+        .map(|event| event.summary.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summaries,
+        [
+            "Standup",
+            "Standup",
+            "Review <draft> & notes",
+            "Standup",
+            "Standup",
+            "Standup"
+        ]
+    );
+    assert_eq!(
+        events[0].start,
+        EventTime::from(Utc.with_ymd_and_hms(2024, 12, 2, 1, 30, 0).unwrap())
+    );
+    let tasks = output
+        .todos
+        .iter()
+        .map(|todo| todo.summary.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(tasks, ["Hand in the report"]);
+
+    // One query for events and one for tasks
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    for (request, component) in requests.iter().zip(["VEVENT", "VTODO"]) {
+        assert_eq!(request.method, "REPORT");
+        assert_eq!(request.header("Depth"), Some("1"));
+        assert!(request
+            .body
+            .contains(&format!(r#"<C:comp-filter name="{}">"#, component)));
+        assert!(request
+            .body
+            .contains(r#"<C:time-range start="20241201T000000Z" end="20250101T000000Z"/>"#));
+    }
+}
+
+#[test]
+fn errors() {
+    let server = Server::start();
+    let home = format!("{}/dav/calendars/alice/", server.url);
+
+    let mut wrong_password = CalDavClient::new(TcpTransport::new()).basic_auth("alice", "guess");
+    assert!(matches!(
+        wrong_password.calendars(&home),
+        Err(CalDavError::Status(401))
+    ));
+    assert!(matches!(
+        client().calendars(&format!("{}/nowhere/", server.url)),
+        Err(CalDavError::Status(404))
+    ));
+
+    let mut parser = IcsParser::new(None, None);
+    let now = Utc.with_ymd_and_hms(2024, 12, 1, 0, 0, 0).unwrap();
+    assert!(matches!(
+        client().query(
+            &format!("{}/dav/calendars/alice/broken/", server.url),
+            now,
+            now,
+            &mut parser
+        ),
+        Err(CalDavError::InvalidXml(_))
+    ));
+
+    // Only plain HTTP
+    assert!(matches!(
+        client().calendars("https://example.com/dav/"),
+        Err(CalDavError::Transport(_))
+    ));
+}
@@ -9,7 +9,7 @@ use std::{
     time::Duration,
 };
 
-use chrono::{Datelike, NaiveTime};
+use chrono::{DateTime, Datelike, NaiveTime, Utc};
 use chrono_tz::Tz;
 use const_random::const_random;
 use embedded_graphics::{
@@ -37,9 +37,10 @@ use esp_idf_svc::{
 use gui::{
     draw::DrawError,
     font,
-    page::main_page::{Event, MainPage},
+    page::main_page::{Event, MainPage, Task},
     text::Text as GuiText,
 };
+use ics_parser::{EventMerger, Snapshot};
 use serde::{Deserialize, Serialize};
 use u8g2_fonts::{
     types::{HorizontalAlignment, VerticalPosition},
@@ -48,12 +49,15 @@ use u8g2_fonts::{
 
 use crate::{
     board::Board,
-    calendar::IcsDownloader,
     common::{get_time, NVS_NAMESPACE},
     display::{create_display, Black},
     http::create_https_client,
+    source::{CalendarEvents, SourceConfig},
 };
 
+// The default NVS partition is 24 KB, shared with the settings
+const MAX_SNAPSHOT_SIZE: usize = 8 * 1024;
+
 #[derive(Debug)]
 pub enum Mode {
     Initialize,
@@ -96,15 +100,103 @@ pub struct AppSettings {
     pub timezone: Tz,
     // Misc
     pub refresh_interval: Duration,
-    // Calendar
-    pub calendar_url: Vec<String>, // Calendar URL max 8
+    // Calendar, the first firmware stored a list of URLs as `calendarUrl`
+    #[serde(alias = "calendarUrl")]
+    pub calendars: Vec<CalendarSettings>, // Calendar max 8
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", from = "StoredCalendarSettings")]
+pub struct CalendarSettings {
+    pub source: SourceConfig,
+    pub options: CalendarOptions,
+}
+
+// Settings saved before calendar sources only had the URL of an ICS feed,
+// or before calendars had options
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredCalendarSettings {
+    Source {
+        source: SourceConfig,
+        #[serde(default)]
+        options: CalendarOptions,
+    },
+    Url {
+        url: String,
+        #[serde(default)]
+        options: CalendarOptions,
+    },
+    Plain(String),
+}
+
+impl From<StoredCalendarSettings> for CalendarSettings {
+    fn from(stored: StoredCalendarSettings) -> Self {
+        match stored {
+            StoredCalendarSettings::Source { source, options } => {
+                CalendarSettings { source, options }
+            }
+            StoredCalendarSettings::Url { url, options } => CalendarSettings {
+                source: SourceConfig::Ics { url },
+                options,
+            },
+            StoredCalendarSettings::Plain(url) => CalendarSettings {
+                source: SourceConfig::Ics { url },
+                options: CalendarOptions::default(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CalendarOptions {
+    pub hide_cancelled: bool,
+    // Hide invitations declined by `attendee_email`
+    pub hide_declined: bool,
+    pub attendee_email: Option<String>,
+    // Hide events marked as free time
+    pub hide_transparent: bool,
+}
+
+impl Default for CalendarOptions {
+    fn default() -> Self {
+        let attendee_email = env!("ICS_ATTENDEE_EMAIL");
+
+        CalendarOptions {
+            hide_cancelled: true,
+            hide_declined: true,
+            attendee_email: (!attendee_email.is_empty()).then(|| attendee_email.to_string()),
+            hide_transparent: false,
+        }
+    }
+}
+
+impl CalendarOptions {
+    pub fn is_visible(&self, event: &ics_parser::Event) -> bool {
+        if self.hide_cancelled && event.is_cancelled() {
+            return false;
+        }
+        if self.hide_transparent && event.is_transparent() {
+            return false;
+        }
+        match &self.attendee_email {
+            Some(email) if self.hide_declined => !event.is_declined_by(email),
+            _ => true,
+        }
+    }
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
-        let calendar_url = env!("ICS_URL")
+        let calendars = env!("ICS_URL")
             .split(";")
-            .map(|s| String::from(s))
+            .map(|url| CalendarSettings {
+                source: SourceConfig::Ics {
+                    url: String::from(url),
+                },
+                options: CalendarOptions::default(),
+            })
             .collect();
 
         AppSettings {
@@ -115,7 +207,7 @@ impl Default for AppSettings {
             },
             timezone: chrono_tz::Asia::Taipei,
             refresh_interval: Duration::from_mins(5),
-            calendar_url,
+            calendars,
         }
     }
 }
@@ -351,11 +443,100 @@ impl NormalMode {
         Ok(())
     }
 
+    // Downloads and merges the calendars, `None` when every download failed. The flag is
+    // set when every download succeeded.
+    fn download_calendars(
+        &mut self,
+        now: DateTime<Utc>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        timezone: Tz,
+    ) -> (Option<Snapshot>, bool, Vec<String>) {
+        // The same event may be in several calendars
+        let mut merger = EventMerger::new();
+        let mut todos = Vec::new();
+        let mut download_errors = 0;
+        let mut warnings = Vec::new();
+        let calendars = &self.app.settings.as_ref().unwrap().calendars;
+        for (index, calendar) in calendars.iter().enumerate() {
+            let url = calendar.source.url();
+            println!("Downloading calendar from: {}", url);
+            let mut source = calendar.source.create(&mut self.http_client);
+
+            let events = CalendarEvents::new(now, timezone, calendar.options.clone());
+            match source.events(start, end, timezone, events) {
+                Ok(parsed_ics) => {
+                    for diagnostic in &parsed_ics.diagnostics {
+                        eprintln!("{}: {}", url, diagnostic);
+                    }
+                    if parsed_ics.omitted_diagnostics > 0 {
+                        eprintln!("{}: {} more problems", url, parsed_ics.omitted_diagnostics);
+                    }
+                    // The name of the feed reads better than its position
+                    let calendar_name = match &parsed_ics.calendar.name {
+                        Some(name) => name.clone(),
+                        None => format!("calendar {}", index + 1),
+                    };
+                    println!("Calendar: {}", calendar_name);
+                    if parsed_ics.skipped_events > 0 {
+                        warnings.push(format!(
+                            "{} events skipped in {}",
+                            parsed_ics.skipped_events, calendar_name
+                        ));
+                    }
+                    // Open tasks with a due date are counted down with the events
+                    todos.extend(
+                        parsed_ics
+                            .todos
+                            .into_iter()
+                            .filter(|todo| todo.is_open() && todo.due.is_some()),
+                    );
+                    // Hidden events are merged too, a newer copy may cancel an older one
+                    merger.add(url, parsed_ics.events.into_events());
+                }
+                Err(e) => {
+                    eprintln!("Downloading calendar from {} failed: {:?}", url, e);
+                    download_errors += 1;
+                    continue;
+                }
+            }
+        }
+        if download_errors > 0 {
+            warnings.insert(
+                0,
+                format!("Failed to download {} ics calendars", download_errors),
+            );
+            if download_errors == calendars.len() {
+                return (None, false, warnings);
+            }
+        }
+        // The calendar of the kept copy decides whether the event is shown
+        let mut events = merger
+            .finish()
+            .into_iter()
+            .filter(|merged| {
+                calendars
+                    .iter()
+                    .find(|calendar| calendar.source.url() == merged.source)
+                    .map_or(true, |calendar| calendar.options.is_visible(&merged.event))
+            })
+            .map(|merged| merged.event)
+            .collect::<Vec<_>>();
+        events.sort_by_key(|event| event.start.to_utc(&timezone));
+
+        let snapshot = Snapshot {
+            created: now,
+            events,
+            todos,
+        };
+        (Some(snapshot), download_errors == 0, warnings)
+    }
+
     fn run_internal(&mut self) -> Result<(), AppError> {
-        // Initialize the app mode
-        self.initialize()?;
+        // Initialize the app mode, the network may be down
+        let initialized = self.initialize();
 
-        // Get the current time
+        // Get the current time, deep sleep keeps the clock running without NTP
         let timezone = self.app.settings.as_ref().unwrap().timezone;
         let now = get_time();
         let now_local = now.with_timezone(&timezone).naive_local();
@@ -374,38 +555,105 @@ impl NormalMode {
             .to_utc()
             + chrono::Duration::days(30);
 
-        let (events, download_errors) = {
-            let mut events = Vec::new();
-            let mut errors = Vec::new();
-            for url in &self.app.settings.as_ref().unwrap().calendar_url {
-                println!("Downloading ICS from: {}", url);
-                // Create ICS Downloader
-                let mut ics_downloader = IcsDownloader::new(
-                    &mut self.http_client,
-                    &url,
-                    month_start.into(),
-                    next_30_days.into(),
-                );
-
-                match ics_downloader.download_and_parse_ics() {
-                    Ok(parsed_ics) => events.extend(parsed_ics),
-                    Err(e) => {
-                        eprintln!("Downloading ics from {} failed.", url);
-                        errors.push(e);
-                        continue;
+        // Without a network or any calendar, the events of the last refresh are shown
+        let offline_warning = |snapshot: &Snapshot| {
+            format!(
+                "Offline, showing events from {}",
+                snapshot
+                    .created
+                    .with_timezone(&timezone)
+                    .format("%m/%d %H:%M")
+            )
+        };
+        let (snapshot, warnings) = match initialized {
+            Ok(()) => match self.download_calendars(now, month_start, next_30_days, timezone) {
+                // A partial snapshot would replace the events of the calendars that failed
+                (Some(snapshot), false, warnings) => (snapshot, warnings),
+                (Some(snapshot), true, mut warnings) => {
+                    if let Err(e) = self.app.save_snapshot(&snapshot) {
+                        log::warn!("Failed to save snapshot: {:?}", e);
+                        warnings.push("Failed to save events for offline use".to_string());
                     }
+                    (snapshot, warnings)
                 }
+                (None, _, mut warnings) => match self.app.load_snapshot()? {
+                    Some(snapshot) => {
+                        warnings.push(offline_warning(&snapshot));
+                        (snapshot, warnings)
+                    }
+                    None => (
+                        Snapshot {
+                            created: now,
+                            events: Vec::new(),
+                            todos: Vec::new(),
+                        },
+                        warnings,
+                    ),
+                },
+            },
+            Err(e) => {
+                log::warn!("Network unavailable: {:?}", e);
+                let snapshot = self.app.load_snapshot()?.ok_or(e)?;
+                let warnings = vec![offline_warning(&snapshot)];
+                (snapshot, warnings)
             }
-            events.sort();
+        };
+
+        let (events, tasks, reminder, next_alarm) = {
+            let events = snapshot.events;
             println!("Events: {:#?}", events);
 
-            // Create a list of activities
+            let tasks = snapshot
+                .todos
+                .iter()
+                .filter_map(|todo| {
+                    let due = todo.due?.date(&timezone);
+                    Some(Task::new(todo.summary.trim(), due))
+                })
+                .collect::<Vec<Task>>();
+
+            // The next event whose reminder has already gone off
+            let reminder = events
+                .iter()
+                .filter(|event| event.start.to_utc(&timezone) > now)
+                .find(|event| event.alarms.iter().any(|alarm| alarm.time <= now))
+                .map(|event| {
+                    let start = event.start.to_utc(&timezone).with_timezone(&timezone);
+                    if event.is_all_day() {
+                        format!(
+                            "Reminder: {} on {}",
+                            event.summary.trim(),
+                            start.format("%m/%d")
+                        )
+                    } else {
+                        format!(
+                            "Reminder: {} at {}",
+                            event.summary.trim(),
+                            start.format("%H:%M")
+                        )
+                    }
+                });
+            let next_alarm = events
+                .iter()
+                .filter_map(|event| event.next_alarm(now))
+                .map(|alarm| alarm.time)
+                .min();
+
+            // Create a list of activities, events already running are shown from today on
             let events_gui = events
                 .iter()
-                .map(|event| Event::new(&event.summary.trim(), event.start.naive_local().date()))
+                .map(|event| {
+                    let first_day = event.first_day(&timezone);
+                    let date = if event.last_day(&timezone) >= now_local.date() {
+                        first_day.max(now_local.date())
+                    } else {
+                        first_day
+                    };
+                    Event::new(&event.summary.trim(), date)
+                })
                 .collect::<Vec<Event>>();
 
-            (events_gui, errors)
+            (events_gui, tasks, reminder, next_alarm)
         };
 
         // Create a new main page
@@ -416,31 +664,44 @@ impl NormalMode {
 
         // Set activities
         main_page.set_events(events);
+        main_page.set_tasks(tasks);
 
         // Render the main page
         main_page.draw(&mut self.app.display)?;
 
-        // Display download errors
-        if download_errors.len() > 0 {
-            let display = &mut self.app.display;
-            let font = FontRenderer::new::<font::inter_bold_16_16>();
-            GuiText::new(
-                &format!("Failed to download {} ics calendars", download_errors.len()),
-                &font,
-            )
-            .x(gui::WIDTH as i32)
-            .y(gui::HEIGHT as i32)
-            .horizontal_align(HorizontalAlignment::Right)
-            .vertical_pos(VerticalPosition::Bottom)
-            .draw(display, Black)?;
+        // Display download errors and skipped events, the last one at the bottom.
+        // Calendar names and summaries come from the feeds and may have glyphs the font lacks.
+        let display = &mut self.app.display;
+        let font = FontRenderer::new::<font::inter_bold_16_16>().with_ignore_unknown_chars(true);
+        for (line, warning) in warnings.iter().rev().enumerate() {
+            GuiText::new(warning, &font)
+                .x(gui::WIDTH as i32)
+                .y(gui::HEIGHT as i32 - line as i32 * 20)
+                .horizontal_align(HorizontalAlignment::Right)
+                .vertical_pos(VerticalPosition::Bottom)
+                .draw(display, Black)?;
+        }
+
+        // Display the reminder at the bottom left
+        if let Some(reminder) = reminder {
+            GuiText::new(&reminder, &font)
+                .x(35)
+                .y(gui::HEIGHT as i32)
+                .vertical_pos(VerticalPosition::Bottom)
+                .draw(display, Black)?;
         }
 
         // Update and display the frame
         self.app.update_and_display()?;
 
-        // Sleep for a while
-        self.app
-            .sleep(self.app.settings.as_ref().unwrap().refresh_interval)?;
+        // Sleep for a while, waking up early for the next reminder
+        let refresh_interval = self.app.settings.as_ref().unwrap().refresh_interval;
+        let sleep_time = next_alarm
+            .and_then(|next_alarm| (next_alarm - now).to_std().ok())
+            .map_or(refresh_interval, |until_alarm| {
+                until_alarm.min(refresh_interval)
+            });
+        self.app.sleep(sleep_time)?;
 
         Ok(())
     }
@@ -568,11 +829,17 @@ impl App {
     }
 
     fn load_settings(&mut self) -> Result<(), AppError> {
-        let mut buf = [0u8; 1024];
+        // Up to the 4000 bytes NVS allows for a string, CalDAV sources take more room
+        let mut buf = vec![0u8; 4000];
         let str = self.nvs_storage.get_str("settings", &mut buf)?;
+        let mut outdated = false;
         self.settings = match str {
-            Some(str) => match serde_json::from_str(str) {
-                Ok(settings) => Some(settings),
+            Some(str) => match serde_json::from_str::<AppSettings>(str) {
+                Ok(settings) => {
+                    // e.g. the `calendarUrl` list of the first firmware
+                    outdated = serde_json::to_string(&settings)? != str;
+                    Some(settings)
+                }
                 Err(e) => {
                     log::warn!("Failed to parse settings: {:?}", e);
                     None
@@ -581,6 +848,12 @@ impl App {
             None => None,
         };
 
+        // Settings of an older firmware are saved again in the current format
+        if outdated {
+            log::info!("Migrating settings");
+            self.save_settings()?;
+        }
+
         Ok(())
     }
 
@@ -591,6 +864,52 @@ impl App {
         Ok(())
     }
 
+    // The events of the last refresh, `None` if there are none or they cannot be read
+    fn load_snapshot(&mut self) -> Result<Option<Snapshot>, AppError> {
+        let Some(len) = self.nvs_storage.blob_len("snapshot")? else {
+            return Ok(None);
+        };
+        let mut buf = vec![0u8; len];
+        let snapshot = match self.nvs_storage.get_blob("snapshot", &mut buf)? {
+            Some(bytes) => match Snapshot::decode(bytes) {
+                Ok(snapshot) => Some(snapshot),
+                Err(e) => {
+                    log::warn!("Failed to read snapshot: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        Ok(snapshot)
+    }
+
+    fn save_snapshot(&mut self, snapshot: &Snapshot) -> Result<(), AppError> {
+        let mut snapshot = snapshot.clone();
+        let mut bytes = snapshot.encode();
+        // The latest events, then the tasks, are left out when the snapshot does not fit
+        while bytes.len() > MAX_SNAPSHOT_SIZE {
+            if snapshot.events.pop().is_none() && snapshot.todos.pop().is_none() {
+                break;
+            }
+            bytes = snapshot.encode();
+        }
+
+        // Flash wears with every write, an unchanged snapshot keeps the time it was first saved
+        if let Some(saved) = self.load_snapshot()? {
+            let unchanged = Snapshot {
+                created: saved.created,
+                ..snapshot
+            };
+            if unchanged.encode() == saved.encode() {
+                return Ok(());
+            }
+        }
+        self.nvs_storage.set_blob("snapshot", &bytes)?;
+
+        Ok(())
+    }
+
     fn clear_settings(&mut self) -> Result<(), AppError> {
         self.nvs_storage.remove("settings")?;
         Ok(())
@@ -1,74 +1,81 @@
+use std::io;
+
+use caldav::{CalDavClient, Request, Response, Transport};
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+
+use embedded_svc::http::{client::Client, Headers, Method};
+use esp_idf_svc::{
+    http::client::EspHttpConnection,
+    io::{Read, Write},
+};
+use ics_parser::ParseOutput;
+
+use crate::source::{create_parser, CalendarEvents, CalendarSource};
 
-use embedded_svc::http::client::Client;
-use esp_idf_svc::{http::client::EspHttpConnection, io::Read};
-use ics_parser::{Event, IcsParser};
+// A jCal feed is read as a whole and its JSON tree takes a few times its size, unlike an ICS
+// feed it is not bounded by `CalendarEvents`. Larger ones are refused before the heap runs out.
+const MAX_JCAL_SIZE: usize = 16 * 1024;
 
-pub struct IcsDownloader<'a> {
+// An ICS feed, or a jCal one depending on the Content-Type
+pub struct IcsSource<'a> {
     http_client: &'a mut Client<EspHttpConnection>,
     url: String,
-    start_date: Option<DateTime<Utc>>,
-    end_date: Option<DateTime<Utc>>,
 }
 
-impl<'a> IcsDownloader<'a> {
-    pub fn new(
-        http_client: &'a mut Client<EspHttpConnection>,
-        url: &str,
-        start_date: Option<DateTime<Utc>>,
-        end_date: Option<DateTime<Utc>>,
-    ) -> Self {
+impl<'a> IcsSource<'a> {
+    pub fn new(http_client: &'a mut Client<EspHttpConnection>, url: &str) -> Self {
         Self {
             http_client,
             url: url.to_string(),
-            start_date,
-            end_date,
         }
     }
+}
 
-    pub fn download_and_parse_ics(&mut self) -> anyhow::Result<Vec<Event>> {
+impl CalendarSource for IcsSource<'_> {
+    fn events(
+        &mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        timezone: Tz,
+        events: CalendarEvents,
+    ) -> anyhow::Result<ParseOutput<CalendarEvents>> {
         let url = self.url.clone();
         let request = self.http_client.get(&url)?;
         let response = request.submit()?;
 
         if (200..=299).contains(&response.status()) {
+            let jcal = is_jcal(response.content_type());
             let mut buf = [0_u8; 256];
             let mut reader = response;
-            let mut offset = 0;
-            let mut leftover = String::new();
 
-            let mut parser = IcsParser::new(self.start_date, self.end_date);
+            let mut parser = create_parser(start, end, timezone, events);
 
+            // A jCal document can only be parsed as a whole
+            let mut json = Vec::new();
             loop {
-                if let Ok(size) = Read::read(&mut reader, &mut buf[offset..]) {
+                if let Ok(size) = Read::read(&mut reader, &mut buf) {
                     if size == 0 {
                         break;
                     }
-                    let size_plus_offset = size + offset;
-                    let text = match std::str::from_utf8(&buf[..size_plus_offset]) {
-                        Ok(text) => {
-                            leftover.push_str(text);
-                            std::mem::take(&mut leftover)
-                        }
-                        Err(error) => {
-                            let valid_up_to = error.valid_up_to();
-                            unsafe {
-                                leftover
-                                    .push_str(std::str::from_utf8_unchecked(&buf[..valid_up_to]));
-                            }
-                            buf.copy_within(valid_up_to.., 0);
-                            offset = size_plus_offset - valid_up_to;
-                            continue;
+                    if jcal {
+                        if json.len() + size > MAX_JCAL_SIZE {
+                            return Err(anyhow::anyhow!(
+                                "jCal feed larger than {} bytes",
+                                MAX_JCAL_SIZE
+                            ));
                         }
-                    };
-
-                    // self.parse_ics_chunk(&mut current_event, &text, &mut leftover);
-                    parser.parse_ics_chunk(&text);
-                    offset = 0;
+                        json.extend_from_slice(&buf[..size]);
+                    } else {
+                        parser.parse_ics_bytes(&buf[..size])?;
+                    }
                 }
             }
+            if jcal {
+                parser.parse_jcal(std::str::from_utf8(&json)?)?;
+            }
 
-            return Ok(parser.get_events());
+            return Ok(parser.finish()?);
         } else {
             // eprintln!("Unexpected response code: {}", response.status());
             return Err(anyhow::anyhow!(
@@ -78,3 +85,102 @@ impl<'a> IcsDownloader<'a> {
         }
     }
 }
+
+// Feeds in jCal (RFC 7265) are JSON, anything else is read as text/calendar
+fn is_jcal(content_type: Option<&str>) -> bool {
+    let Some(content_type) = content_type else {
+        return false;
+    };
+    let mime_type = content_type.split(';').next().unwrap_or_default().trim();
+    mime_type.eq_ignore_ascii_case("application/calendar+json")
+        || mime_type.eq_ignore_ascii_case("application/json")
+}
+
+// A calendar collection on a CalDAV server, e.g. Nextcloud or Radicale
+pub struct CalDavSource<'a> {
+    client: CalDavClient<EspTransport<'a>>,
+    url: String,
+}
+
+impl<'a> CalDavSource<'a> {
+    pub fn new(
+        http_client: &'a mut Client<EspHttpConnection>,
+        url: &str,
+        username: &str,
+        password: &str,
+    ) -> Self {
+        Self {
+            client: CalDavClient::new(EspTransport(http_client)).basic_auth(username, password),
+            url: url.to_string(),
+        }
+    }
+}
+
+impl CalendarSource for CalDavSource<'_> {
+    fn events(
+        &mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        timezone: Tz,
+        events: CalendarEvents,
+    ) -> anyhow::Result<ParseOutput<CalendarEvents>> {
+        let mut parser = create_parser(start, end, timezone, events);
+        let count = self.client.query(&self.url, start, end, &mut parser)?;
+        println!("Calendar objects: {}", count);
+        Ok(parser.finish()?)
+    }
+}
+
+// Sends the CalDAV requests with the HTTPS client of the device
+struct EspTransport<'a>(&'a mut Client<EspHttpConnection>);
+
+impl Transport for EspTransport<'_> {
+    fn send(&mut self, request: &Request) -> io::Result<Response<'_>> {
+        let method = match request.method {
+            "PROPFIND" => Method::Propfind,
+            "REPORT" => Method::Report,
+            method => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("unsupported method {}", method),
+                ))
+            }
+        };
+        let content_length = request.body.len().to_string();
+        let mut headers = request
+            .headers
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect::<Vec<_>>();
+        headers.push(("Content-Length", content_length.as_str()));
+
+        let mut http_request = self
+            .0
+            .request(method, &request.url, &headers)
+            .map_err(io::Error::other)?;
+        http_request
+            .write_all(request.body.as_bytes())
+            .map_err(io::Error::other)?;
+        http_request.flush().map_err(io::Error::other)?;
+        let response = http_request.submit().map_err(io::Error::other)?;
+
+        Ok(Response {
+            status: response.status(),
+            // Read while the report is parsed rather than held in memory
+            body: Box::new(EspBody(response)),
+        })
+    }
+}
+
+// The body of an `EspHttpConnection` response as `std::io::Read`
+struct EspBody<R>(R);
+
+impl<R> io::Read for EspBody<R>
+where
+    R: Read,
+    R::Error: std::error::Error + Send + Sync + 'static,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf).map_err(io::Error::other)
+    }
+}
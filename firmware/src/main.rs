@@ -12,6 +12,7 @@ pub mod calendar;
 pub mod common;
 pub mod display;
 pub mod http;
+pub mod source;
 
 fn main() {
     // It is necessary to call this function once. Otherwise some patches to the runtime
@@ -0,0 +1,138 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use embedded_svc::http::client::Client;
+use esp_idf_svc::http::client::EspHttpConnection;
+use ics_parser::{EarliestEvents, Event, EventSink, IcsParser, ParseOutput};
+use serde::{Deserialize, Serialize};
+
+use crate::app::CalendarOptions;
+use crate::calendar::{CalDavSource, IcsSource};
+
+// Events kept per calendar, so a large feed cannot exhaust the heap
+const MAX_EVENTS: usize = 100;
+
+// The events of a calendar, past and hidden events are kept apart so they cannot take the
+// place of upcoming ones. Hidden events are still merged, a newer copy may hide an older one.
+pub struct CalendarEvents {
+    now: DateTime<Utc>,
+    timezone: Tz,
+    options: CalendarOptions,
+    upcoming: EarliestEvents,
+    past: EarliestEvents,
+    hidden: EarliestEvents,
+}
+
+impl CalendarEvents {
+    pub fn new(now: DateTime<Utc>, timezone: Tz, options: CalendarOptions) -> Self {
+        Self {
+            now,
+            timezone,
+            options,
+            upcoming: EarliestEvents::new(MAX_EVENTS),
+            past: EarliestEvents::new(MAX_EVENTS),
+            hidden: EarliestEvents::new(MAX_EVENTS),
+        }
+    }
+
+    pub fn into_events(self) -> Vec<Event> {
+        let mut events = self.past.into_sorted_vec();
+        events.extend(self.upcoming.into_sorted_vec());
+        events.extend(self.hidden.into_sorted_vec());
+        events
+    }
+}
+
+impl EventSink for CalendarEvents {
+    fn push(&mut self, event: Event) {
+        if !self.options.is_visible(&event) {
+            self.hidden.push(event);
+        } else if event.end.to_utc(&self.timezone) < self.now {
+            self.past.push(event);
+        } else {
+            self.upcoming.push(event);
+        }
+    }
+}
+
+// Somewhere the events of a calendar come from. `NormalMode` only sees the events,
+// so a new backend needs a `SourceConfig` variant and an implementation of this.
+pub trait CalendarSource {
+    // The events overlapping `start..end`, with the tasks and problems of the calendar
+    fn events(
+        &mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        timezone: Tz,
+        events: CalendarEvents,
+    ) -> anyhow::Result<ParseOutput<CalendarEvents>>;
+}
+
+// How to reach a calendar, stored in the settings as e.g.
+// `{"type": "ics", "url": "https://example.com/calendar.ics"}`
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SourceConfig {
+    // An ICS or jCal feed downloaded as a whole
+    Ics {
+        url: String,
+    },
+    // A calendar collection on a CalDAV server, only the events of the window are downloaded
+    #[serde(rename_all = "camelCase")]
+    CalDav {
+        url: String,
+        username: String,
+        password: String,
+    },
+}
+
+// The settings are logged at boot, the password is left out
+impl fmt::Debug for SourceConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceConfig::Ics { url } => f.debug_struct("Ics").field("url", url).finish(),
+            SourceConfig::CalDav { url, username, .. } => f
+                .debug_struct("CalDav")
+                .field("url", url)
+                .field("username", username)
+                .field("password", &"***")
+                .finish(),
+        }
+    }
+}
+
+impl SourceConfig {
+    // Identifies the calendar in logs and when merging events
+    pub fn url(&self) -> &str {
+        match self {
+            SourceConfig::Ics { url } | SourceConfig::CalDav { url, .. } => url,
+        }
+    }
+
+    pub fn create<'a>(
+        &self,
+        http_client: &'a mut Client<EspHttpConnection>,
+    ) -> Box<dyn CalendarSource + 'a> {
+        match self {
+            SourceConfig::Ics { url } => Box::new(IcsSource::new(http_client, url)),
+            SourceConfig::CalDav {
+                url,
+                username,
+                password,
+            } => Box::new(CalDavSource::new(http_client, url, username, password)),
+        }
+    }
+}
+
+// A parser for the events of a source
+pub fn create_parser(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    timezone: Tz,
+    events: CalendarEvents,
+) -> IcsParser<CalendarEvents> {
+    IcsParser::with_sink(Some(start), Some(end), events)
+        .default_timezone(timezone)
+        .decode_html_entities(true)
+}
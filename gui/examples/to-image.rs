@@ -5,7 +5,7 @@ use epd_waveshare::{
     color::Color::{self},
     epd7in5_v2::{HEIGHT, WIDTH},
 };
-use gui::page::main_page::{Event, MainPage};
+use gui::page::main_page::{Event, MainPage, Task};
 
 fn main() -> anyhow::Result<()> {
     let mut display: SimulatorDisplay<Color> = SimulatorDisplay::new(Size::new(
@@ -38,10 +38,17 @@ fn main() -> anyhow::Result<()> {
         ),
     ];
 
+    let tasks = vec![
+        Task::new("Lab report", date.with_day0(date.day0() - 2).unwrap()),
+        Task::new("Project proposal", date.with_day0(date.day0() + 3).unwrap()),
+    ];
+
     let mut main_page = MainPage::new(now);
 
     main_page.set_events(events);
 
+    main_page.set_tasks(tasks);
+
     main_page.set_weekday(weekday);
 
     main_page.draw(&mut display)?;
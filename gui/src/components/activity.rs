@@ -69,14 +69,16 @@ where
         .y(activity_y);
 
         // Draw the activity name
-        if activity.days_remaining == 0 {
+        if activity.days_remaining <= 0 {
             name_text.draw(display, Black)?;
         } else {
             name_text.draw_gray(display, GRAY_LUMA)?;
         }
 
         // If the activity is today, display "Today", otherwise display the number of days remaining
-        let days_remaining_text = if activity.days_remaining == 0 {
+        let days_remaining_text = if activity.days_remaining < 0 {
+            "Overdue"
+        } else if activity.days_remaining == 0 {
             "Today"
         } else {
             if activity.days_remaining == 1 {
@@ -92,8 +94,8 @@ where
             .y(activity_y)
             .horizontal_align(HorizontalAlignment::Right);
 
-        // If the activity is today, draw it in black, otherwise draw it in gray
-        if activity.days_remaining == 0 {
+        // If the activity is today or overdue, draw it in black, otherwise draw it in gray
+        if activity.days_remaining <= 0 {
             days_text.draw(display, Black)?;
         } else {
             days_text.draw_gray(display, GRAY_LUMA)?;
@@ -25,10 +25,26 @@ impl Event {
     }
 }
 
+// An open task, counted down to its due date in the activity list
+pub struct Task {
+    name: String,
+    due: NaiveDate,
+}
+
+impl Task {
+    pub fn new(name: &str, due: NaiveDate) -> Self {
+        Self {
+            name: name.to_string(),
+            due,
+        }
+    }
+}
+
 pub struct MainPage {
     pub weekday: String,
     pub now: NaiveDateTime,
     pub events: Vec<Event>,
+    pub tasks: Vec<Task>,
 }
 
 impl MainPage {
@@ -37,6 +53,7 @@ impl MainPage {
             now,
             weekday: String::new(),
             events: vec![],
+            tasks: vec![],
         }
     }
 
@@ -48,6 +65,10 @@ impl MainPage {
         self.events = events;
     }
 
+    pub fn set_tasks(&mut self, tasks: Vec<Task>) {
+        self.tasks = tasks;
+    }
+
     pub fn draw<Display>(&self, display: &mut Display) -> Result<(), DrawError>
     where
         Display: DrawTarget<Color = Color>,
@@ -76,19 +97,29 @@ impl MainPage {
         // Draw the calendar component
         draw_calendar(display, 35, 121, date, &events_date)?;
 
-        let days_remaining = self
+        // Past events are left out, overdue tasks are still to be done
+        let mut activities = self
             .events
             .iter()
-            .filter_map(|event| {
-                let days_remaining = event.date.signed_duration_since(date).num_days();
-                if days_remaining >= 0 {
-                    Some(DaysRemaining::new(
-                        &event.name,
-                        days_remaining.try_into().unwrap(),
-                    ))
-                } else {
-                    None
-                }
+            .map(|event| {
+                (
+                    &event.name,
+                    event.date.signed_duration_since(date).num_days(),
+                )
+            })
+            .filter(|(_, days_remaining)| *days_remaining >= 0)
+            .chain(
+                self.tasks
+                    .iter()
+                    .map(|task| (&task.name, task.due.signed_duration_since(date).num_days())),
+            )
+            .collect::<Vec<_>>();
+        activities.sort_by_key(|(_, days_remaining)| *days_remaining);
+
+        let days_remaining = activities
+            .into_iter()
+            .map(|(name, days_remaining)| {
+                DaysRemaining::new(name, days_remaining.try_into().unwrap())
             })
             .collect();
 
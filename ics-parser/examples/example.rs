@@ -15,9 +15,13 @@ fn main() {
 
     let mut parser = ics_parser::IcsParser::new(None, None);
     for chunk in data {
-        parser.parse_ics_chunk(&chunk);
+        parser.parse_ics_chunk(&chunk).unwrap();
     }
-    let events = parser.get_events();
+    let output = parser.finish().unwrap();
 
-    println!("{:#?}", events);
+    println!("{:#?}", output.calendar);
+    println!("{:#?}", output.events);
+    for diagnostic in output.diagnostics {
+        eprintln!("{}", diagnostic);
+    }
 }
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+
+use crate::{datetime::DateTimeValue, duration::Duration};
+
+// Upper bound of REPEAT, so a broken feed cannot produce endless reminders
+const MAX_REPETITIONS: u32 = 100;
+
+// ACTION of a VALARM
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AlarmAction {
+    Display,
+    Audio,
+    Email,
+    // Experimental or IANA registered values
+    Other(String),
+}
+
+impl AlarmAction {
+    pub(crate) fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "DISPLAY" => AlarmAction::Display,
+            "AUDIO" => AlarmAction::Audio,
+            "EMAIL" => AlarmAction::Email,
+            _ => AlarmAction::Other(value.trim().to_string()),
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            AlarmAction::Display => "DISPLAY",
+            AlarmAction::Audio => "AUDIO",
+            AlarmAction::Email => "EMAIL",
+            AlarmAction::Other(value) => value,
+        }
+    }
+}
+
+// A reminder of an event instance, one for every repetition of a VALARM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alarm {
+    pub action: AlarmAction,
+    pub description: Option<String>,
+    pub time: DateTime<Utc>,
+}
+
+// TRIGGER of a VALARM
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    // Relative to the start, or to the end with `RELATED=END`
+    Start(Duration),
+    End(Duration),
+    Absolute(DateTime<Utc>),
+}
+
+// A VALARM before it is resolved against the instances of its event
+#[derive(Debug, Clone, Default)]
+pub struct TemporaryAlarm {
+    pub action: Option<AlarmAction>,
+    pub description: Option<String>,
+    pub trigger: Option<Trigger>,
+    // Extra repetitions, `duration` apart
+    pub repeat: u32,
+    pub duration: Option<Duration>,
+}
+
+impl TemporaryAlarm {
+    // The reminders of the instance between `start` and `end`. Both are date-times, so that
+    // a trigger before an all-day event lands on the evening before.
+    pub fn resolve(&self, start: &DateTimeValue, end: &DateTimeValue) -> Vec<Alarm> {
+        let first = match self.trigger {
+            Some(Trigger::Start(offset)) => offset.add_to(start).and_then(|time| time.to_utc()),
+            Some(Trigger::End(offset)) => offset.add_to(end).and_then(|time| time.to_utc()),
+            Some(Trigger::Absolute(time)) => Some(time),
+            None => None,
+        };
+        let Some(first) = first else {
+            return Vec::new();
+        };
+
+        // REPEAT is only valid together with DURATION
+        let (repeat, interval) = match self.duration {
+            Some(duration) => (
+                self.repeat.min(MAX_REPETITIONS),
+                ChronoDuration::try_days(duration.days)
+                    .zip(ChronoDuration::try_seconds(duration.seconds))
+                    .and_then(|(days, seconds)| days.checked_add(&seconds)),
+            ),
+            None => (0, Some(ChronoDuration::zero())),
+        };
+        // Repetitions past the range of dates are dropped
+        (0..=repeat)
+            .map_while(|repetition| {
+                let offset = interval?.checked_mul(repetition as i32)?;
+                first.checked_add_signed(offset)
+            })
+            .map(|time| Alarm {
+                action: self.action.clone().unwrap_or(AlarmAction::Display),
+                description: self.description.clone(),
+                time,
+            })
+            .collect()
+    }
+}
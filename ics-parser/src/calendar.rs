@@ -0,0 +1,29 @@
+// Properties of the VCALENDAR itself
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CalendarInfo {
+    // X-WR-CALNAME, a readable name of the feed
+    pub name: Option<String>,
+    // X-WR-CALDESC
+    pub description: Option<String>,
+    // X-APPLE-CALENDAR-COLOR, e.g. `#1BADF8`
+    pub color: Option<String>,
+    // X-WR-TIMEZONE as written, also when it is not a known timezone
+    pub timezone: Option<String>,
+    // PRODID, the application that wrote the feed
+    pub product_id: Option<String>,
+    // METHOD, e.g. `PUBLISH`
+    pub method: Option<String>,
+}
+
+impl CalendarInfo {
+    // The color as red, green and blue, from `#RRGGBB` or `#RRGGBBAA`
+    pub fn rgb(&self) -> Option<(u8, u8, u8)> {
+        let hex = self.color.as_deref()?.trim().strip_prefix('#')?;
+        if !(hex.len() == 6 || hex.len() == 8) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+        Some((channel(0)?, channel(2)?, channel(4)?))
+    }
+}
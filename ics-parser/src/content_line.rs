@@ -0,0 +1,85 @@
+// RFC 5545 3.1: an unfolded line, `NAME *(";" PARAM) ":" VALUE`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentLine<'a> {
+    // Names are case insensitive, kept in upper case
+    pub name: String,
+    pub parameters: Vec<Parameter>,
+    // Still escaped, as written in the feed
+    pub value: &'a str,
+}
+
+// e.g. `MEMBER="mailto:a@example.com","mailto:b@example.com"`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parameter {
+    pub name: String,
+    // Without the quotes, a quoted value may contain `;`, `:` and `,`
+    pub values: Vec<String>,
+}
+
+impl<'a> ContentLine<'a> {
+    // None when the line is not a content line, e.g. the value separator is missing
+    pub fn parse(line: &'a str) -> Option<Self> {
+        let end = line.find([';', ':'])?;
+        let name = token(&line[..end])?;
+
+        let mut parameters = Vec::new();
+        let mut rest = &line[end..];
+        while let Some(parameter) = rest.strip_prefix(';') {
+            let (name, mut values_str) = parameter.split_once('=')?;
+            let mut values = Vec::new();
+            loop {
+                let (value, next) = parameter_value(values_str)?;
+                values.push(value.to_string());
+                match next.strip_prefix(',') {
+                    Some(next) => values_str = next,
+                    None => {
+                        rest = next;
+                        break;
+                    }
+                }
+            }
+            parameters.push(Parameter {
+                name: token(name)?,
+                values,
+            });
+        }
+
+        Some(Self {
+            name,
+            parameters,
+            value: rest.strip_prefix(':')?,
+        })
+    }
+
+    // The first value of a parameter
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameter_values(name).first().map(String::as_str)
+    }
+
+    pub fn parameter_values(&self, name: &str) -> &[String] {
+        self.parameters
+            .iter()
+            .find(|parameter| parameter.name.eq_ignore_ascii_case(name))
+            .map_or(&[], |parameter| parameter.values.as_slice())
+    }
+}
+
+// A property or parameter name: letters, digits and dashes
+fn token(name: &str) -> Option<String> {
+    let name = name.trim();
+    if name.is_empty() || !name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+        return None;
+    }
+    Some(name.to_ascii_uppercase())
+}
+
+// One parameter value and what follows it
+fn parameter_value(value: &str) -> Option<(&str, &str)> {
+    if let Some(quoted) = value.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        Some((&quoted[..end], &quoted[end + 1..]))
+    } else {
+        let end = value.find([',', ';', ':'])?;
+        Some((&value[..end], &value[end..]))
+    }
+}
@@ -0,0 +1,137 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+use crate::{
+    content_line::ContentLine,
+    diagnostic::DiagnosticKind,
+    event::EventTime,
+    timezone::{TimeZones, Zone},
+};
+
+// A DATE or DATE-TIME value as written in the feed, before it is resolved to UTC.
+// The wall clock time is kept so recurrences can be expanded in local time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DateTimeValue {
+    Date(NaiveDate),
+    Utc(NaiveDateTime),
+    Zoned(NaiveDateTime, Zone),
+    Floating(NaiveDateTime),
+}
+
+impl DateTimeValue {
+    // The wall clock time of the value, dates start at midnight
+    pub fn naive(&self) -> NaiveDateTime {
+        match self {
+            DateTimeValue::Date(date) => date.and_hms_opt(0, 0, 0).unwrap(),
+            DateTimeValue::Utc(naive)
+            | DateTimeValue::Zoned(naive, _)
+            | DateTimeValue::Floating(naive) => *naive,
+        }
+    }
+
+    // The same kind of value (and timezone) at another wall clock time
+    pub fn with_naive(&self, naive: NaiveDateTime) -> Self {
+        match self {
+            DateTimeValue::Date(_) => DateTimeValue::Date(naive.date()),
+            DateTimeValue::Utc(_) => DateTimeValue::Utc(naive),
+            DateTimeValue::Zoned(_, zone) => DateTimeValue::Zoned(naive, zone.clone()),
+            DateTimeValue::Floating(_) => DateTimeValue::Floating(naive),
+        }
+    }
+
+    pub fn to_utc(&self) -> Option<DateTime<Utc>> {
+        match self {
+            DateTimeValue::Zoned(naive, zone) => zone.to_utc(naive),
+            // Floating times are treated as UTC when no default timezone is known
+            _ => Some(self.naive().and_utc()),
+        }
+    }
+
+    pub fn to_event_time(&self) -> Option<EventTime> {
+        match self {
+            DateTimeValue::Date(date) => Some(EventTime::Date(*date)),
+            _ => self.to_utc().map(EventTime::DateTime),
+        }
+    }
+}
+
+pub fn parse_datetime(
+    line: &ContentLine,
+    timezones: &TimeZones,
+) -> Result<DateTimeValue, DiagnosticKind> {
+    parse_datetime_with_params(line, line.value, timezones)
+}
+
+// EXDATE and RDATE may hold several comma separated values sharing the same parameters
+pub fn parse_datetime_list(
+    line: &ContentLine,
+    timezones: &TimeZones,
+) -> Result<Vec<DateTimeValue>, DiagnosticKind> {
+    line.value
+        .split(',')
+        .map(|value| parse_datetime_with_params(line, value, timezones))
+        .collect()
+}
+
+fn parse_datetime_with_params(
+    line: &ContentLine,
+    value: &str,
+    timezones: &TimeZones,
+) -> Result<DateTimeValue, DiagnosticKind> {
+    // Only the start of a PERIOD value is used
+    let value = value.split('/').next().unwrap_or(value).trim();
+    let invalid = || DiagnosticKind::InvalidValue(value.to_string());
+
+    if line
+        .parameter("VALUE")
+        .is_some_and(|kind| kind.eq_ignore_ascii_case("DATE"))
+    {
+        let naive_date = NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|_| invalid())?;
+        return Ok(DateTimeValue::Date(naive_date));
+    }
+    if let Some(tzid) = line.parameter("TZID") {
+        let timezone = timezones
+            .resolve(tzid)
+            .ok_or_else(|| DiagnosticKind::UnknownTimezone(tzid.to_string()))?;
+        let naive_datetime =
+            NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+        return Ok(DateTimeValue::Zoned(naive_datetime, timezone));
+    }
+
+    match parse_datetime_value(value).ok_or_else(invalid)? {
+        DateTimeValue::Floating(naive) => match &timezones.floating {
+            Some(zone) => Ok(DateTimeValue::Zoned(naive, zone.clone())),
+            None => Ok(DateTimeValue::Floating(naive)),
+        },
+        value => Ok(value),
+    }
+}
+
+// Whether two values refer to the same recurrence instance
+pub fn same_instance(a: &DateTimeValue, b: &DateTimeValue) -> bool {
+    match (a, b) {
+        (DateTimeValue::Date(_), _) | (_, DateTimeValue::Date(_)) => {
+            a.naive().date() == b.naive().date()
+        }
+        _ => a.to_utc() == b.to_utc(),
+    }
+}
+
+pub fn parse_datetime_value(value: &str) -> Option<DateTimeValue> {
+    if value.ends_with('Z') {
+        if let Ok(utc_datetime) = DateTime::parse_from_rfc3339(value) {
+            Some(DateTimeValue::Utc(utc_datetime.naive_utc()))
+        } else {
+            iso8601::datetime(value)
+                .ok()?
+                .into_naive()
+                .map(DateTimeValue::Utc)
+        }
+    } else if let Ok(naive_datetime) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        Some(DateTimeValue::Floating(naive_datetime))
+    } else {
+        // UNTIL and RDATE may hold a bare date
+        NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .map(DateTimeValue::Date)
+    }
+}
@@ -0,0 +1,67 @@
+use std::{error::Error, fmt};
+
+// Why a property could not be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    // A TZID that is neither an IANA or Windows name nor defined by a VTIMEZONE
+    UnknownTimezone(String),
+    // The value does not have the format of the property
+    InvalidValue(String),
+    // A VEVENT without DTSTART
+    MissingStart,
+    // A component still open when its parent ends or the feed is cut off
+    MissingEnd,
+    // An END line without a matching BEGIN
+    UnexpectedEnd,
+    // Not a `NAME;PARAM=value:value` line
+    MalformedLine,
+    // A jCal document that is not JSON
+    InvalidJson(String),
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticKind::UnknownTimezone(tzid) => write!(f, "unknown timezone `{}`", tzid),
+            DiagnosticKind::InvalidValue(value) => write!(f, "invalid value `{}`", value),
+            DiagnosticKind::MissingStart => write!(f, "missing DTSTART"),
+            DiagnosticKind::MissingEnd => write!(f, "missing END"),
+            DiagnosticKind::UnexpectedEnd => write!(f, "END without BEGIN"),
+            DiagnosticKind::MalformedLine => write!(f, "malformed content line"),
+            DiagnosticKind::InvalidJson(error) => write!(f, "invalid JSON, {}", error),
+        }
+    }
+}
+
+// Diagnostics kept by a parser, the problems past these are only counted. A broken feed
+// may have one on every line.
+pub const MAX_DIAGNOSTICS: usize = 100;
+
+// A problem found while parsing a feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    // 1-based, the first physical line of a folded property
+    pub line: usize,
+    pub property: String,
+    pub kind: DiagnosticKind,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}: {}", self.line, self.property, self.kind)
+    }
+}
+
+// Returned by a strict parser for the first problem in the feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub diagnostic: Diagnostic,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to parse calendar, {}", self.diagnostic)
+    }
+}
+
+impl Error for ParseError {}
@@ -0,0 +1,77 @@
+use chrono::{Duration as ChronoDuration, NaiveDate};
+
+use crate::datetime::DateTimeValue;
+
+// RFC 5545 3.3.6: a DURATION value. Days and weeks are nominal (the wall clock time is
+// kept across DST changes), hours, minutes and seconds are exact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Duration {
+    pub days: i64,
+    pub seconds: i64,
+}
+
+impl Duration {
+    // e.g. `PT1H`, `P1D`, `P2W`, `-PT15M`
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (sign, value) = match value.as_bytes().first()? {
+            b'-' => (-1, &value[1..]),
+            b'+' => (1, &value[1..]),
+            _ => (1, value),
+        };
+
+        let duration = match iso8601::duration(value).ok()? {
+            iso8601::Duration::Weeks(weeks) => Self {
+                days: sign * i64::from(weeks) * 7,
+                seconds: 0,
+            },
+            // Years and months are not allowed, their length depends on the date
+            iso8601::Duration::YMDHMS {
+                year: 0,
+                month: 0,
+                day,
+                hour,
+                minute,
+                second,
+                ..
+            } => Self {
+                days: sign * i64::from(day),
+                seconds: sign
+                    * (i64::from(hour) * 3600 + i64::from(minute) * 60 + i64::from(second)),
+            },
+            _ => return None,
+        };
+
+        // Dates in a feed have four digit years, a longer duration could not be added to them
+        let last_date = NaiveDate::from_ymd_opt(9999, 12, 31)?;
+        let max_days = NaiveDate::MAX.signed_duration_since(last_date).num_days();
+        let days = duration.days.abs() + duration.seconds.abs() / 86_400;
+        (days <= max_days).then_some(duration)
+    }
+
+    // The end of an event lasting this long, `None` past the range of dates
+    pub fn add_to(&self, start: &DateTimeValue) -> Option<DateTimeValue> {
+        let naive = start
+            .naive()
+            .checked_add_signed(ChronoDuration::try_days(self.days)?)?;
+        match start {
+            // Only whole days make sense for dates
+            DateTimeValue::Date(_) => {
+                let days = ChronoDuration::try_days(self.seconds.div_euclid(86_400))?;
+                Some(DateTimeValue::Date(naive.checked_add_signed(days)?.date()))
+            }
+            // The exact part is added to the instant, not to the wall clock time
+            DateTimeValue::Zoned(..) if self.seconds != 0 => {
+                let instant = start.with_naive(naive).to_utc()?;
+                let instant =
+                    instant.checked_add_signed(ChronoDuration::try_seconds(self.seconds)?)?;
+                Some(DateTimeValue::Utc(instant.naive_utc()))
+            }
+            _ => {
+                Some(start.with_naive(
+                    naive.checked_add_signed(ChronoDuration::try_seconds(self.seconds)?)?,
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::alarm::Alarm;
+
+// The start or end of an event, all-day events only have a date
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventTime {
+    Date(NaiveDate),
+    DateTime(DateTime<Utc>),
+}
+
+impl EventTime {
+    pub fn is_date(&self) -> bool {
+        matches!(self, EventTime::Date(_))
+    }
+
+    // The instant in a timezone, dates start at local midnight
+    pub fn to_utc(&self, tz: &Tz) -> DateTime<Utc> {
+        match self {
+            EventTime::Date(date) => {
+                let midnight = date.and_time(NaiveTime::MIN);
+                tz.from_local_datetime(&midnight)
+                    .earliest()
+                    .map(|datetime| datetime.to_utc())
+                    // No local midnight on this day, e.g. a DST change at 00:00
+                    .unwrap_or_else(|| (midnight - tz.offset_from_utc_date(date).fix()).and_utc())
+            }
+            EventTime::DateTime(datetime) => *datetime,
+        }
+    }
+
+    // The calendar date in a timezone
+    pub fn date(&self, tz: &Tz) -> NaiveDate {
+        match self {
+            EventTime::Date(date) => *date,
+            EventTime::DateTime(datetime) => datetime.with_timezone(tz).date_naive(),
+        }
+    }
+
+    // Dates compare as UTC midnight when sorted against date-times
+    fn sort_key(&self) -> NaiveDateTime {
+        match self {
+            EventTime::Date(date) => date.and_time(NaiveTime::MIN),
+            EventTime::DateTime(datetime) => datetime.naive_utc(),
+        }
+    }
+}
+
+impl From<NaiveDate> for EventTime {
+    fn from(date: NaiveDate) -> Self {
+        EventTime::Date(date)
+    }
+}
+
+impl From<DateTime<Utc>> for EventTime {
+    fn from(datetime: DateTime<Utc>) -> Self {
+        EventTime::DateTime(datetime)
+    }
+}
+
+impl PartialOrd for EventTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for EventTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+// STATUS of a VEVENT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventStatus {
+    Tentative,
+    Confirmed,
+    Cancelled,
+}
+
+impl EventStatus {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "TENTATIVE" => Some(EventStatus::Tentative),
+            "CONFIRMED" => Some(EventStatus::Confirmed),
+            "CANCELLED" => Some(EventStatus::Cancelled),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            EventStatus::Tentative => "TENTATIVE",
+            EventStatus::Confirmed => "CONFIRMED",
+            EventStatus::Cancelled => "CANCELLED",
+        }
+    }
+}
+
+// TRANSP, whether the event takes up time on the calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transparency {
+    Opaque,
+    Transparent,
+}
+
+impl Transparency {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "OPAQUE" => Some(Transparency::Opaque),
+            "TRANSPARENT" => Some(Transparency::Transparent),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Transparency::Opaque => "OPAQUE",
+            Transparency::Transparent => "TRANSPARENT",
+        }
+    }
+}
+
+// CLASS, the access classification of the event
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Classification {
+    Public,
+    Private,
+    Confidential,
+    // Experimental or IANA registered values
+    Other(String),
+}
+
+impl Classification {
+    pub(crate) fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "PUBLIC" => Classification::Public,
+            "PRIVATE" => Classification::Private,
+            "CONFIDENTIAL" => Classification::Confidential,
+            _ => Classification::Other(value.trim().to_string()),
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Classification::Public => "PUBLIC",
+            Classification::Private => "PRIVATE",
+            Classification::Confidential => "CONFIDENTIAL",
+            Classification::Other(value) => value,
+        }
+    }
+}
+
+// PARTSTAT of an attendee
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParticipationStatus {
+    NeedsAction,
+    Accepted,
+    Declined,
+    Tentative,
+    Delegated,
+}
+
+impl ParticipationStatus {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "NEEDS-ACTION" => Some(ParticipationStatus::NeedsAction),
+            "ACCEPTED" => Some(ParticipationStatus::Accepted),
+            "DECLINED" => Some(ParticipationStatus::Declined),
+            "TENTATIVE" => Some(ParticipationStatus::Tentative),
+            "DELEGATED" => Some(ParticipationStatus::Delegated),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            ParticipationStatus::NeedsAction => "NEEDS-ACTION",
+            ParticipationStatus::Accepted => "ACCEPTED",
+            ParticipationStatus::Declined => "DECLINED",
+            ParticipationStatus::Tentative => "TENTATIVE",
+            ParticipationStatus::Delegated => "DELEGATED",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attendee {
+    // The calendar address, e.g. `mailto:someone@example.com`
+    pub address: String,
+    pub common_name: Option<String>,
+    pub participation_status: ParticipationStatus,
+}
+
+impl Attendee {
+    // The address without the `mailto:` scheme
+    pub fn email(&self) -> &str {
+        match self.address.get(..7) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("mailto:") => &self.address[7..],
+            _ => &self.address,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub uid: String,
+    pub summary: String,
+    pub start: EventTime,
+    // Exclusive, the day after the last day for all-day events
+    pub end: EventTime,
+    // The original start of an instance of a recurring event
+    pub recurrence_id: Option<EventTime>,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    // Moodle puts the course name here
+    pub categories: Vec<String>,
+    pub status: Option<EventStatus>,
+    pub transparency: Option<Transparency>,
+    pub class: Option<Classification>,
+    pub url: Option<String>,
+    // The calendar address, e.g. `mailto:someone@example.com`
+    pub organizer: Option<String>,
+    pub attendees: Vec<Attendee>,
+    pub sequence: u32,
+    pub last_modified: Option<DateTime<Utc>>,
+    // `X-` properties by name, e.g. `X-MICROSOFT-CDO-BUSYSTATUS`
+    pub x_properties: HashMap<String, String>,
+    // Reminders of this instance, the earliest first
+    pub alarms: Vec<Alarm>,
+}
+
+impl Event {
+    pub fn new(summary: &str, start: EventTime, end: EventTime) -> Self {
+        Self {
+            uid: String::new(),
+            summary: summary.to_string(),
+            start,
+            end,
+            recurrence_id: None,
+            description: None,
+            location: None,
+            categories: Vec::new(),
+            status: None,
+            transparency: None,
+            class: None,
+            url: None,
+            organizer: None,
+            attendees: Vec::new(),
+            sequence: 0,
+            last_modified: None,
+            x_properties: HashMap::new(),
+            alarms: Vec::new(),
+        }
+    }
+
+    pub fn is_all_day(&self) -> bool {
+        self.start.is_date()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.status == Some(EventStatus::Cancelled)
+    }
+
+    // Free time, the event does not block the calendar
+    pub fn is_transparent(&self) -> bool {
+        self.transparency == Some(Transparency::Transparent)
+    }
+
+    // The attendee with an email address, compared case-insensitively
+    pub fn attendee(&self, email: &str) -> Option<&Attendee> {
+        self.attendees
+            .iter()
+            .find(|attendee| attendee.email().eq_ignore_ascii_case(email.trim()))
+    }
+
+    pub fn is_declined_by(&self, email: &str) -> bool {
+        self.attendee(email)
+            .is_some_and(|attendee| attendee.participation_status == ParticipationStatus::Declined)
+    }
+
+    // The first reminder after an instant, e.g. to plan the next wake-up
+    pub fn next_alarm(&self, after: DateTime<Utc>) -> Option<&Alarm> {
+        self.alarms.iter().find(|alarm| alarm.time > after)
+    }
+
+    // The first local calendar day of the event
+    pub fn first_day(&self, tz: &Tz) -> NaiveDate {
+        self.start.date(tz)
+    }
+
+    // The last local calendar day of the event, an end at midnight does not cover the next day
+    pub fn last_day(&self, tz: &Tz) -> NaiveDate {
+        let first_day = self.first_day(tz);
+        let last_day = match self.end {
+            EventTime::Date(end) => end.pred_opt().unwrap_or(end),
+            EventTime::DateTime(end) => {
+                let local_end = end.with_timezone(tz);
+                if local_end.time() == NaiveTime::MIN && end > self.start.to_utc(tz) {
+                    local_end.date_naive().pred_opt().unwrap_or(first_day)
+                } else {
+                    local_end.date_naive()
+                }
+            }
+        };
+        last_day.max(first_day)
+    }
+
+    // Every local calendar day the event covers
+    pub fn days(&self, tz: &Tz) -> Vec<NaiveDate> {
+        let last_day = self.last_day(tz);
+        self.first_day(tz)
+            .iter_days()
+            .take_while(|day| *day <= last_day)
+            .collect()
+    }
+}
+
+// 實作 PartialEq 來比較兩個 Event 是否相等
+impl PartialEq for Event {
+    fn eq(&self, other: &Self) -> bool {
+        self.start == other.start
+    }
+}
+
+// 實作 Eq trait 表示這個比較是可反射的
+impl Eq for Event {}
+
+// 實作 PartialOrd 來定義如何比較兩個 Event
+// Same as `Ord`, so `a < b` means `a` starts later. Compare `start` for earlier or later.
+impl PartialOrd for Event {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// 實作 Ord 來提供完整的排序功能
+impl Ord for Event {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // 由近到遠排序，所以用 other 比較 self (降序)
+        other.start.cmp(&self.start)
+    }
+}
@@ -0,0 +1,141 @@
+use serde_json::{Map, Value};
+
+use crate::text::{escape_text, quote_parameter};
+
+// RFC 7265: jCal is the same calendar as JSON. Every component is
+// `[name, [properties], [components]]` and every property `[name, {parameters}, type, values...]`.
+// The document is turned back into content lines, so the text parser does the rest.
+// A property that cannot be read is an `Err` with its name.
+pub fn content_lines(document: &Value) -> Vec<Result<String, String>> {
+    let mut lines = Vec::new();
+    match document.as_array().map(Vec::as_slice) {
+        // Some servers return several calendars at once
+        Some([Value::Array(_), ..]) => {
+            for component in document.as_array().into_iter().flatten() {
+                component_lines(component, &mut lines);
+            }
+        }
+        _ => component_lines(document, &mut lines),
+    }
+    lines
+}
+
+fn component_lines(component: &Value, lines: &mut Vec<Result<String, String>>) {
+    let Some([Value::String(name), Value::Array(properties), Value::Array(components)]) =
+        component.as_array().map(Vec::as_slice)
+    else {
+        lines.push(Err("BEGIN".to_string()));
+        return;
+    };
+
+    let name = name.to_ascii_uppercase();
+    lines.push(Ok(format!("BEGIN:{}", name)));
+    for property in properties {
+        lines.push(property_line(property));
+    }
+    for component in components {
+        component_lines(component, lines);
+    }
+    lines.push(Ok(format!("END:{}", name)));
+}
+
+fn property_line(property: &Value) -> Result<String, String> {
+    let Some([Value::String(name), Value::Object(parameters), Value::String(kind), values @ ..]) =
+        property.as_array().map(Vec::as_slice)
+    else {
+        let name = property.get(0).and_then(Value::as_str).unwrap_or_default();
+        return Err(name.to_ascii_uppercase());
+    };
+    let name = name.to_ascii_uppercase();
+    let kind = kind.to_ascii_lowercase();
+
+    let mut line = name;
+    for (parameter, value) in parameters {
+        let values = match value {
+            Value::Array(values) => values.iter().map(scalar).collect::<Vec<_>>(),
+            value => vec![scalar(value)],
+        };
+        let values = values
+            .iter()
+            .map(|value| quote_parameter(value))
+            .collect::<Vec<_>>();
+        line.push(';');
+        line.push_str(&parameter.to_ascii_uppercase());
+        line.push('=');
+        line.push_str(&values.join(","));
+    }
+    // The text parser only needs the type where a property can have several
+    if matches!(kind.as_str(), "date" | "date-time" | "period")
+        && !parameters
+            .keys()
+            .any(|key| key.eq_ignore_ascii_case("VALUE"))
+    {
+        line.push_str(";VALUE=");
+        line.push_str(&kind.to_ascii_uppercase());
+    }
+
+    let values = values
+        .iter()
+        .map(|value| match value {
+            // Structured values, e.g. REQUEST-STATUS
+            Value::Array(parts) => parts
+                .iter()
+                .map(|part| value_text(&kind, part))
+                .collect::<Vec<_>>()
+                .join(";"),
+            value => value_text(&kind, value),
+        })
+        .collect::<Vec<_>>();
+    line.push(':');
+    line.push_str(&values.join(","));
+    Ok(line)
+}
+
+// A single value in its text form
+fn value_text(kind: &str, value: &Value) -> String {
+    match (kind, value) {
+        ("text", Value::String(text)) => escape_text(text),
+        // `2024-12-05T10:00:00Z` is `20241205T100000Z`, also in periods
+        ("date" | "date-time" | "period", Value::String(text)) => text.replace([':', '-'], ""),
+        // `-05:00` is `-0500`
+        ("time" | "utc-offset", Value::String(text)) => text.replace(':', ""),
+        ("recur", Value::Object(rule)) => recurrence_rule(rule),
+        ("boolean", Value::Bool(true)) => "TRUE".to_string(),
+        ("boolean", Value::Bool(false)) => "FALSE".to_string(),
+        (_, value) => scalar(value),
+    }
+}
+
+// e.g. `{"freq": "WEEKLY", "byday": ["MO", "WE"]}` is `FREQ=WEEKLY;BYDAY=MO,WE`
+fn recurrence_rule(rule: &Map<String, Value>) -> String {
+    let mut parts = rule
+        .iter()
+        .map(|(key, value)| {
+            let values = match value {
+                Value::Array(values) => values.iter().map(scalar).collect::<Vec<_>>(),
+                value => vec![scalar(value)],
+            };
+            let mut values = values.join(",");
+            if key.eq_ignore_ascii_case("until") {
+                values = values.replace([':', '-'], "");
+            }
+            (key.to_ascii_uppercase(), values)
+        })
+        .collect::<Vec<_>>();
+    // FREQ comes first in the text form
+    parts.sort_by_key(|(key, _)| key != "FREQ");
+
+    parts
+        .into_iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
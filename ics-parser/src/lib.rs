@@ -1,176 +1,974 @@
-use std::cmp::Ordering;
+use std::collections::HashMap;
 
-use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use alarm::{TemporaryAlarm, Trigger};
+use chrono::{DateTime, NaiveTime, Utc};
 use chrono_tz::Tz;
+use datetime::{
+    parse_datetime, parse_datetime_list, parse_datetime_value, same_instance, DateTimeValue,
+};
+use duration::Duration;
+use rrule::RecurrenceRule;
+use timezone::{parse_utc_offset, CustomZone, Observance, TimeZones, Zone};
 
-#[derive(Debug, Clone)]
-struct TemporaryEvent {
-    pub summary: String,
-    pub start: Option<DateTime<Utc>>,
-    pub end: Option<DateTime<Utc>>,
-}
+pub use alarm::{Alarm, AlarmAction};
+pub use calendar::CalendarInfo;
+pub use content_line::{ContentLine, Parameter};
+pub use diagnostic::{Diagnostic, DiagnosticKind, ParseError, MAX_DIAGNOSTICS};
+pub use event::{
+    Attendee, Classification, Event, EventStatus, EventTime, ParticipationStatus, Transparency,
+};
+pub use merge::{EventMerger, MergedEvent};
+pub use sink::{EarliestEvents, EventSink};
+pub use snapshot::{Snapshot, SnapshotError, SNAPSHOT_VERSION};
+pub use todo::{Todo, TodoStatus};
+pub use writer::{to_ics_string, IcsWriter};
 
-#[derive(Debug, Clone)]
-pub struct Event {
+mod alarm;
+mod calendar;
+mod content_line;
+mod datetime;
+mod diagnostic;
+mod duration;
+mod event;
+#[cfg(feature = "jcal")]
+mod jcal;
+mod merge;
+mod rrule;
+mod sink;
+mod snapshot;
+mod text;
+mod timezone;
+mod todo;
+mod windows_zones;
+mod writer;
+
+// Upper bound of occurrences generated for a recurring event without an end
+const MAX_OCCURRENCES: usize = 1000;
+
+#[derive(Debug, Clone, Default)]
+struct TemporaryEvent {
+    pub uid: String,
     pub summary: String,
-    pub start: DateTime<Utc>,
-    pub end: DateTime<Utc>,
+    pub start: Option<DateTimeValue>,
+    pub end: Option<DateTimeValue>,
+    pub duration: Option<Duration>,
+    pub rrule: Option<RecurrenceRule>,
+    pub rdates: Vec<DateTimeValue>,
+    pub exdates: Vec<DateTimeValue>,
+    pub recurrence_id: Option<DateTimeValue>,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub categories: Vec<String>,
+    pub status: Option<EventStatus>,
+    pub transparency: Option<Transparency>,
+    pub class: Option<Classification>,
+    pub url: Option<String>,
+    pub organizer: Option<String>,
+    pub attendees: Vec<Attendee>,
+    pub sequence: u32,
+    pub last_modified: Option<DateTime<Utc>>,
+    pub x_properties: HashMap<String, String>,
+    pub alarms: Vec<TemporaryAlarm>,
+    // A property of the event could not be parsed
+    pub invalid: bool,
 }
 
-// 實作 PartialEq 來比較兩個 Event 是否相等
-impl PartialEq for Event {
-    fn eq(&self, other: &Self) -> bool {
-        self.start == other.start
+impl TemporaryEvent {
+    fn is_recurring(&self) -> bool {
+        self.rrule.is_some() || !self.rdates.is_empty()
     }
-}
-
-// 實作 Eq trait 表示這個比較是可反射的
-impl Eq for Event {}
 
-// 實作 PartialOrd 來定義如何比較兩個 Event
-impl PartialOrd for Event {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        // 由近到遠排序，所以用 other 比較 self (降序)
-        Some(self.start.cmp(&other.start))
+    fn to_event(&self, start: EventTime, end: EventTime) -> Event {
+        Event {
+            uid: self.uid.clone(),
+            summary: self.summary.clone(),
+            start,
+            end,
+            recurrence_id: self
+                .recurrence_id
+                .as_ref()
+                .and_then(|recurrence_id| recurrence_id.to_event_time()),
+            description: self.description.clone(),
+            location: self.location.clone(),
+            categories: self.categories.clone(),
+            status: self.status,
+            transparency: self.transparency,
+            class: self.class.clone(),
+            url: self.url.clone(),
+            organizer: self.organizer.clone(),
+            attendees: self.attendees.clone(),
+            sequence: self.sequence,
+            last_modified: self.last_modified,
+            x_properties: self.x_properties.clone(),
+            // Resolved for each instance by the parser
+            alarms: Vec::new(),
+        }
     }
-}
 
-// 實作 Ord 來提供完整的排序功能
-impl Ord for Event {
-    fn cmp(&self, other: &Self) -> Ordering {
-        // 由近到遠排序，所以用 other 比較 self (降序)
-        other.start.cmp(&self.start)
+    // The end of the instance starting at `start`. Without DTEND or DURATION, all-day
+    // events last one day and others have no length (RFC 5545 3.6.1).
+    fn end_at(&self, start: &DateTimeValue) -> Option<DateTimeValue> {
+        match (&self.end, &self.start, self.duration) {
+            // Shift the end by the same wall clock time as the start
+            (Some(end), Some(first_start), _) => {
+                let shift = start.naive() - first_start.naive();
+                Some(end.with_naive(end.naive().checked_add_signed(shift)?))
+            }
+            (_, _, Some(duration)) => duration.add_to(start),
+            _ => match start {
+                DateTimeValue::Date(_) => Duration {
+                    days: 1,
+                    seconds: 0,
+                }
+                .add_to(start),
+                _ => Some(start.clone()),
+            },
+        }
     }
 }
 
-pub struct IcsParser {
+pub struct IcsParser<S = Vec<Event>> {
+    calendar: CalendarInfo,
+    // Names of the open components, the innermost last
+    components: Vec<String>,
     current_event: TemporaryEvent,
+    // None once a property of the task could not be parsed
+    current_todo: Option<Todo>,
+    // None once a property of the alarm could not be parsed
+    current_alarm: Option<TemporaryAlarm>,
+    todos: Vec<Todo>,
     leftover: String,
-    events: Vec<Event>,
+    // The start of a character split across byte chunks
+    partial_char: Vec<u8>,
+    unfolded: String,
+    // Receives every event as soon as it is complete
+    sink: S,
+    // Recurring events and their overridden instances can come in any order,
+    // so they are combined once the whole feed has been read
+    recurring_events: Vec<TemporaryEvent>,
+    overrides: Vec<TemporaryEvent>,
+    timezones: TimeZones,
+    current_timezone: Option<CustomZone>,
+    current_observance: Option<Observance>,
     start_date: Option<DateTime<Utc>>,
     end_date: Option<DateTime<Utc>>,
+    decode_html_entities: bool,
+    strict: bool,
+    // Physical lines seen so far, and the first line of the held back logical line
+    line_number: usize,
+    unfolded_line_number: usize,
+    current_line_number: usize,
+    diagnostics: Vec<Diagnostic>,
+    omitted_diagnostics: usize,
+    skipped_events: usize,
+}
+
+// The result of parsing a whole feed
+#[derive(Debug, Clone)]
+pub struct ParseOutput<S = Vec<Event>> {
+    pub calendar: CalendarInfo,
+    pub events: S,
+    pub todos: Vec<Todo>,
+    pub diagnostics: Vec<Diagnostic>,
+    // Problems past the first `MAX_DIAGNOSTICS`
+    pub omitted_diagnostics: usize,
+    // Events left out because of a problem
+    pub skipped_events: usize,
 }
 
 impl IcsParser {
     pub fn new(start_date: Option<DateTime<Utc>>, end_date: Option<DateTime<Utc>>) -> Self {
+        Self::with_sink(start_date, end_date, Vec::new())
+    }
+}
+
+impl<S: EventSink> IcsParser<S> {
+    // Hand events to a sink instead of collecting them all, recurring events are still
+    // held until the end of the feed because their exceptions may come later
+    pub fn with_sink(
+        start_date: Option<DateTime<Utc>>,
+        end_date: Option<DateTime<Utc>>,
+        sink: S,
+    ) -> Self {
         Self {
-            current_event: TemporaryEvent {
-                summary: String::new(),
-                start: None,
-                end: None,
-            },
+            calendar: CalendarInfo::default(),
+            components: Vec::new(),
+            current_event: TemporaryEvent::default(),
+            current_todo: None,
+            current_alarm: None,
+            todos: Vec::new(),
             leftover: String::new(),
-            events: Vec::new(),
+            partial_char: Vec::new(),
+            unfolded: String::new(),
+            sink,
+            recurring_events: Vec::new(),
+            overrides: Vec::new(),
+            timezones: TimeZones::default(),
+            current_timezone: None,
+            current_observance: None,
             start_date,
             end_date,
+            decode_html_entities: false,
+            strict: false,
+            line_number: 0,
+            unfolded_line_number: 0,
+            current_line_number: 0,
+            diagnostics: Vec::new(),
+            omitted_diagnostics: 0,
+            skipped_events: 0,
         }
     }
 
+    // Timezone of floating times, unless the feed declares one with `X-WR-TIMEZONE`
+    pub fn default_timezone(mut self, timezone: Tz) -> Self {
+        self.timezones.floating = Some(Zone::Iana(timezone));
+        self
+    }
+
+    // Also decode HTML entities (e.g. `&amp;`) left in TEXT values by some providers
+    pub fn decode_html_entities(mut self, decode_html_entities: bool) -> Self {
+        self.decode_html_entities = decode_html_entities;
+        self
+    }
+
+    // Stop at the first problem instead of skipping the events it affects
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
     // 分析ICS片段內容，並處理跨段的未完成行
-    pub fn parse_ics_chunk(&mut self, chunk: &str) {
-        self.leftover.push_str(chunk);
+    pub fn parse_ics_chunk(&mut self, chunk: &str) -> Result<(), ParseError> {
+        let mut buffer = std::mem::take(&mut self.leftover);
+        buffer.push_str(chunk);
 
-        // println!("Leftover: {:?}", self.leftover);
+        // Only complete physical lines are handled, the rest waits for the next chunk
+        let mut rest = buffer.as_str();
+        while let Some(pos) = rest.find('\n') {
+            let line = &rest[..pos];
+            self.unfold_line(line.strip_suffix('\r').unwrap_or(line));
+            rest = &rest[pos + 1..];
+            self.check_strict()?;
+        }
 
-        for line in self.leftover.lines() {
-            if line.starts_with("BEGIN:VEVENT") {
-                self.current_event = TemporaryEvent {
-                    summary: String::new(),
-                    start: None,
-                    end: None,
-                };
-            } else if line.starts_with("SUMMARY:") {
-                self.current_event.summary = line["SUMMARY:".len()..].to_string();
-            } else if line.starts_with("DTSTART") {
-                self.current_event.start = parse_datetime(line);
-            } else if line.starts_with("DTEND") {
-                self.current_event.end = parse_datetime(line);
-            } else if line.starts_with("END:VEVENT") {
-                // println!("Parsed event: {:?}", self.current_event);
-                if let (Some(start_date), Some(end_date)) = (
-                    self.current_event.start.as_ref(),
-                    self.current_event.end.as_ref(),
-                ) {
-                    if let (Some(filter_start_date), Some(filter_end_date)) =
-                        (self.start_date.as_ref(), self.end_date.as_ref())
-                    {
-                        if start_date >= filter_start_date && end_date <= filter_end_date {
-                            self.events.push(Event {
-                                summary: self.current_event.summary.clone(),
-                                start: start_date.clone(),
-                                end: end_date.clone(),
-                            });
+        self.leftover = rest.to_string();
+        Ok(())
+    }
+
+    // Raw bytes, e.g. straight from an HTTP body. A character may be split across chunks,
+    // invalid UTF-8 is replaced with U+FFFD.
+    pub fn parse_ics_bytes(&mut self, chunk: &[u8]) -> Result<(), ParseError> {
+        let mut bytes = std::mem::take(&mut self.partial_char);
+        bytes.extend_from_slice(chunk);
+
+        let mut text = String::with_capacity(bytes.len());
+        let mut rest = bytes.as_slice();
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    break;
+                }
+                Err(error) => {
+                    let (valid, invalid) = rest.split_at(error.valid_up_to());
+                    text.push_str(&String::from_utf8_lossy(valid));
+                    match error.error_len() {
+                        Some(len) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            rest = &invalid[len..];
+                        }
+                        // Incomplete at the end, the rest comes with the next chunk
+                        None => {
+                            self.partial_char = invalid.to_vec();
+                            break;
                         }
-                    } else {
-                        self.events.push(Event {
-                            summary: self.current_event.summary.clone(),
-                            start: start_date.clone(),
-                            end: end_date.clone(),
-                        });
                     }
                 }
             }
         }
 
-        if !self.leftover.ends_with('\n') {
-            self.leftover = self.leftover.lines().last().unwrap_or("").to_string();
+        self.parse_ics_chunk(&text)
+    }
+
+    // A whole jCal (RFC 7265) document, the JSON form of a feed. It gives the same events
+    // as the text form, a diagnostic's line is that of the property in the text form.
+    // The document and its JSON tree are held in memory, a sink only bounds the events.
+    #[cfg(feature = "jcal")]
+    pub fn parse_jcal(&mut self, json: &str) -> Result<(), ParseError> {
+        let document = match serde_json::from_str(json) {
+            Ok(document) => document,
+            // Nothing can be read from it, so this fails also when not strict
+            Err(error) => {
+                let diagnostic = Diagnostic {
+                    line: error.line(),
+                    property: String::new(),
+                    kind: DiagnosticKind::InvalidJson(error.to_string()),
+                };
+                self.push_diagnostic(diagnostic.clone());
+                return Err(ParseError { diagnostic });
+            }
+        };
+
+        for line in jcal::content_lines(&document) {
+            self.line_number += 1;
+            self.current_line_number = self.line_number;
+            match line {
+                Ok(line) => self.parse_line(&line),
+                Err(name) => self.report(&name, DiagnosticKind::MalformedLine),
+            }
+            self.check_strict()?;
+        }
+        Ok(())
+    }
+
+    fn check_strict(&self) -> Result<(), ParseError> {
+        match self.diagnostics.first() {
+            Some(diagnostic) if self.strict => Err(ParseError {
+                diagnostic: diagnostic.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    // Problems found so far
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    // RFC 5545 3.1: a line starting with a space or a tab continues the previous line.
+    // The previous line is held back until the next one is seen, because a fold can
+    // land exactly on a chunk boundary.
+    fn unfold_line(&mut self, line: &str) {
+        self.line_number += 1;
+        if let Some(continuation) = line.strip_prefix([' ', '\t']) {
+            self.unfolded.push_str(continuation);
         } else {
-            self.leftover.clear();
+            let logical_line = std::mem::replace(&mut self.unfolded, line.to_string());
+            self.current_line_number =
+                std::mem::replace(&mut self.unfolded_line_number, self.line_number);
+            if !logical_line.is_empty() {
+                self.parse_line(&logical_line);
+            }
         }
     }
 
-    pub fn get_events(self) -> Vec<Event> {
-        self.events
+    // Process whatever is still buffered once the input is exhausted
+    fn flush(&mut self) {
+        self.finish_unfolding();
+        self.finish_components();
+        self.finish_recurring_events();
+    }
+
+    fn finish_unfolding(&mut self) {
+        // The input ended in the middle of a character
+        if !std::mem::take(&mut self.partial_char).is_empty() {
+            self.leftover.push(char::REPLACEMENT_CHARACTER);
+        }
+
+        let leftover = std::mem::take(&mut self.leftover);
+        if !leftover.is_empty() {
+            self.unfold_line(leftover.strip_suffix('\r').unwrap_or(&leftover));
+        }
+
+        let logical_line = std::mem::take(&mut self.unfolded);
+        self.current_line_number = self.unfolded_line_number;
+        if !logical_line.is_empty() {
+            self.parse_line(&logical_line);
+        }
+    }
+
+    fn parse_line(&mut self, line: &str) {
+        let Some(line) = ContentLine::parse(line) else {
+            self.report(property_name(line), DiagnosticKind::MalformedLine);
+            return;
+        };
+        match line.name.as_str() {
+            "BEGIN" => return self.begin_component(&line.value.trim().to_ascii_uppercase()),
+            "END" => return self.end_component(&line.value.trim().to_ascii_uppercase()),
+            _ => {}
+        }
+
+        // A property belongs to the innermost open component only
+        let result = match self.components.last().map(String::as_str) {
+            None | Some("VCALENDAR") => self.parse_calendar_line(&line),
+            Some("VEVENT") => self.parse_event_line(&line),
+            Some("VTODO") => self.parse_todo_line(&line),
+            Some("VALARM") => self.parse_alarm_line(&line),
+            Some("VTIMEZONE" | "STANDARD" | "DAYLIGHT") => self.parse_timezone_line(&line),
+            // VJOURNAL, ...
+            Some(_) => Ok(()),
+        };
+        if let Err(kind) = result {
+            self.report(&line.name, kind);
+            if self.components.last().is_some_and(|name| name == "VEVENT") {
+                self.current_event.invalid = true;
+            }
+        }
+    }
+
+    fn begin_component(&mut self, name: &str) {
+        let parent = self.components.last().map(String::as_str);
+        match (parent, name) {
+            (_, "VEVENT") => self.current_event = TemporaryEvent::default(),
+            (_, "VTODO") => self.current_todo = Some(Todo::default()),
+            // Alarms of tasks are not used
+            (Some("VEVENT"), "VALARM") => self.current_alarm = Some(TemporaryAlarm::default()),
+            (_, "VTIMEZONE") => self.current_timezone = Some(CustomZone::default()),
+            (Some("VTIMEZONE"), "STANDARD" | "DAYLIGHT") => {
+                self.current_observance = Some(Observance::default())
+            }
+            _ => {}
+        }
+        self.components.push(name.to_string());
+    }
+
+    // Components left open inside the ending one are reported and dropped
+    fn end_component(&mut self, name: &str) {
+        let Some(position) = self.components.iter().rposition(|open| open == name) else {
+            self.report(name, DiagnosticKind::UnexpectedEnd);
+            return;
+        };
+        while self.components.len() > position + 1 {
+            self.abandon_component();
+        }
+        self.components.pop();
+
+        match name {
+            "VEVENT" => self.end_event(),
+            "VTODO" => {
+                if let Some(todo) = self.current_todo.take() {
+                    if self.todo_in_window(&todo) {
+                        self.todos.push(todo);
+                    }
+                }
+            }
+            "VALARM" => {
+                // TRIGGER is required
+                if let Some(alarm) = self.current_alarm.take() {
+                    if alarm.trigger.is_some() {
+                        self.current_event.alarms.push(alarm);
+                    }
+                }
+            }
+            "VTIMEZONE" => {
+                if let Some(timezone) = self.current_timezone.take() {
+                    self.timezones.insert(timezone);
+                }
+            }
+            "STANDARD" | "DAYLIGHT" => {
+                if let (Some(timezone), Some(mut observance)) = (
+                    self.current_timezone.as_mut(),
+                    self.current_observance.take(),
+                ) {
+                    observance.drop_empty_rule();
+                    timezone.observances.push(observance);
+                }
+            }
+            _ => {}
+        }
     }
-}
 
-fn parse_datetime(datetime_str: &str) -> Option<DateTime<Utc>> {
-    if let Some((tzid_part, time_part)) = datetime_str.split_once(':') {
-        if tzid_part.contains("TZID=") {
-            if let Some(tzid) = tzid_part.split('=').nth(1) {
-                let timezone: Tz = tzid.parse().ok()?;
-                let naive_datetime =
-                    NaiveDateTime::parse_from_str(time_part, "%Y%m%dT%H%M%S").ok()?;
-                let datetime_with_timezone =
-                    timezone.from_local_datetime(&naive_datetime).single()?;
-                return Some(datetime_with_timezone.with_timezone(&Utc)); // 使用 with_timezone(&Utc)
-            }
-        } else if tzid_part.contains("VALUE=") {
-            if let Some(date) = tzid_part.split('=').nth(1) {
-                if date == "DATE" {
-                    let naive_date = NaiveDate::parse_from_str(time_part, "%Y%m%d").ok()?;
-                    return Some(naive_date.and_hms_opt(0, 0, 0).unwrap().and_utc());
+    // The feed ended, e.g. a download was cut off
+    fn finish_components(&mut self) {
+        while !self.components.is_empty() {
+            self.abandon_component();
+        }
+    }
+
+    fn abandon_component(&mut self) {
+        let Some(name) = self.components.pop() else {
+            return;
+        };
+        self.report(&name, DiagnosticKind::MissingEnd);
+        match name.as_str() {
+            "VEVENT" => {
+                self.current_event = TemporaryEvent::default();
+                self.skipped_events += 1;
+            }
+            "VTODO" => self.current_todo = None,
+            "VALARM" => self.current_alarm = None,
+            "VTIMEZONE" => self.current_timezone = None,
+            "STANDARD" | "DAYLIGHT" => self.current_observance = None,
+            _ => {}
+        }
+    }
+
+    fn end_event(&mut self) {
+        // println!("Parsed event: {:?}", self.current_event);
+        let event = std::mem::take(&mut self.current_event);
+        if event.start.is_none() && !event.invalid {
+            self.report("VEVENT", DiagnosticKind::MissingStart);
+        }
+        if event.invalid || event.start.is_none() {
+            self.skipped_events += 1;
+        } else if event.recurrence_id.is_some() {
+            self.overrides.push(event);
+        } else if event.is_recurring() {
+            self.recurring_events.push(event);
+        } else {
+            self.finish_event(event);
+        }
+    }
+
+    // Properties of the calendar itself
+    fn parse_calendar_line(&mut self, line: &ContentLine) -> Result<(), DiagnosticKind> {
+        let value = line.value.trim();
+        match line.name.as_str() {
+            "X-WR-CALNAME" => self.calendar.name = Some(self.decode_text(value)),
+            "X-WR-CALDESC" => self.calendar.description = Some(self.decode_text(value)),
+            "X-APPLE-CALENDAR-COLOR" => self.calendar.color = Some(value.to_string()),
+            "PRODID" => self.calendar.product_id = Some(value.to_string()),
+            "METHOD" => self.calendar.method = Some(value.to_string()),
+            "X-WR-TIMEZONE" => {
+                self.calendar.timezone = Some(value.to_string());
+                let zone = self
+                    .timezones
+                    .resolve(value)
+                    .ok_or_else(|| DiagnosticKind::UnknownTimezone(value.to_string()))?;
+                self.timezones.floating = Some(zone);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // Properties of a VEVENT
+    fn parse_event_line(&mut self, line: &ContentLine) -> Result<(), DiagnosticKind> {
+        let value = line.value;
+        let invalid = || DiagnosticKind::InvalidValue(value.trim().to_string());
+
+        match line.name.as_str() {
+            "UID" => self.current_event.uid = value.to_string(),
+            "SUMMARY" => self.current_event.summary = self.decode_text(value),
+            "DTSTART" => self.current_event.start = Some(parse_datetime(line, &self.timezones)?),
+            "DTEND" => self.current_event.end = Some(parse_datetime(line, &self.timezones)?),
+            "DURATION" => {
+                self.current_event.duration = Some(Duration::parse(value).ok_or_else(invalid)?)
+            }
+            "RRULE" => {
+                self.current_event.rrule = Some(RecurrenceRule::parse(value).ok_or_else(invalid)?)
+            }
+            "RDATE" => {
+                let rdates = parse_datetime_list(line, &self.timezones)?;
+                self.current_event.rdates.extend(rdates);
+            }
+            "EXDATE" => {
+                let exdates = parse_datetime_list(line, &self.timezones)?;
+                self.current_event.exdates.extend(exdates);
+            }
+            "RECURRENCE-ID" => {
+                self.current_event.recurrence_id = Some(parse_datetime(line, &self.timezones)?)
+            }
+            "DESCRIPTION" => self.current_event.description = Some(self.decode_text(value)),
+            "LOCATION" => self.current_event.location = Some(self.decode_text(value)),
+            "CATEGORIES" => {
+                let categories = text::split_text_list(value)
+                    .into_iter()
+                    .map(|category| self.decode_text(category.trim()))
+                    .filter(|category| !category.is_empty());
+                self.current_event
+                    .categories
+                    .extend(categories.collect::<Vec<_>>());
+            }
+            "STATUS" => self.current_event.status = EventStatus::parse(value),
+            "TRANSP" => self.current_event.transparency = Transparency::parse(value),
+            "CLASS" => self.current_event.class = Some(Classification::parse(value)),
+            "URL" => self.current_event.url = Some(value.trim().to_string()),
+            "ORGANIZER" => self.current_event.organizer = Some(value.trim().to_string()),
+            "ATTENDEE" => self.current_event.attendees.push(Attendee {
+                address: value.trim().to_string(),
+                common_name: line.parameter("CN").map(str::to_string),
+                participation_status: line
+                    .parameter("PARTSTAT")
+                    .and_then(ParticipationStatus::parse)
+                    .unwrap_or(ParticipationStatus::NeedsAction),
+            }),
+            "SEQUENCE" => {
+                self.current_event.sequence = value.trim().parse().map_err(|_| invalid())?
+            }
+            "LAST-MODIFIED" => {
+                let last_modified = parse_datetime_value(value.trim())
+                    .and_then(|value| value.to_utc())
+                    .ok_or_else(invalid)?;
+                self.current_event.last_modified = Some(last_modified);
+            }
+            name if name.starts_with("X-") => {
+                let value = self.decode_text(value);
+                self.current_event
+                    .x_properties
+                    .insert(name.to_string(), value);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn parse_todo_line(&mut self, line: &ContentLine) -> Result<(), DiagnosticKind> {
+        let Some(mut todo) = self.current_todo.take() else {
+            return Ok(());
+        };
+        // The task is dropped when a property is invalid
+        self.parse_todo_property(&mut todo, line)?;
+        self.current_todo = Some(todo);
+        Ok(())
+    }
+
+    // Properties of a VTODO
+    fn parse_todo_property(
+        &self,
+        todo: &mut Todo,
+        line: &ContentLine,
+    ) -> Result<(), DiagnosticKind> {
+        let value = line.value;
+        let invalid = || DiagnosticKind::InvalidValue(value.trim().to_string());
+        let event_time = || {
+            parse_datetime(line, &self.timezones)?
+                .to_event_time()
+                .ok_or_else(invalid)
+        };
+
+        match line.name.as_str() {
+            "UID" => todo.uid = value.to_string(),
+            "SUMMARY" => todo.summary = self.decode_text(value),
+            "DESCRIPTION" => todo.description = Some(self.decode_text(value)),
+            "DTSTART" => todo.start = Some(event_time()?),
+            "DUE" => todo.due = Some(event_time()?),
+            "COMPLETED" => {
+                let completed = parse_datetime(line, &self.timezones)?.to_utc();
+                todo.completed = Some(completed.ok_or_else(invalid)?);
+            }
+            "PERCENT-COMPLETE" => {
+                let percent = value.trim().parse().ok().filter(|percent| *percent <= 100);
+                todo.percent_complete = Some(percent.ok_or_else(invalid)?);
+            }
+            "PRIORITY" => {
+                let priority = value.trim().parse().ok().filter(|priority| *priority <= 9);
+                todo.priority = priority.ok_or_else(invalid)?;
+            }
+            "STATUS" => todo.status = TodoStatus::parse(value),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn parse_alarm_line(&mut self, line: &ContentLine) -> Result<(), DiagnosticKind> {
+        let Some(mut alarm) = self.current_alarm.take() else {
+            return Ok(());
+        };
+        // The alarm is dropped when a property is invalid, the event is kept
+        self.parse_alarm_property(&mut alarm, line)?;
+        self.current_alarm = Some(alarm);
+        Ok(())
+    }
+
+    // Properties of a VALARM
+    fn parse_alarm_property(
+        &self,
+        alarm: &mut TemporaryAlarm,
+        line: &ContentLine,
+    ) -> Result<(), DiagnosticKind> {
+        let value = line.value;
+        let invalid = || DiagnosticKind::InvalidValue(value.trim().to_string());
+
+        match line.name.as_str() {
+            "ACTION" => alarm.action = Some(AlarmAction::parse(value)),
+            "DESCRIPTION" => alarm.description = Some(self.decode_text(value)),
+            "TRIGGER" => {
+                let absolute = line
+                    .parameter("VALUE")
+                    .is_some_and(|kind| kind.eq_ignore_ascii_case("DATE-TIME"));
+                let related_end = line
+                    .parameter("RELATED")
+                    .is_some_and(|related| related.eq_ignore_ascii_case("END"));
+                alarm.trigger = Some(if absolute {
+                    let time = parse_datetime(line, &self.timezones)?.to_utc();
+                    Trigger::Absolute(time.ok_or_else(invalid)?)
                 } else {
-                    return None;
+                    let offset = Duration::parse(value).ok_or_else(invalid)?;
+                    if related_end {
+                        Trigger::End(offset)
+                    } else {
+                        Trigger::Start(offset)
+                    }
+                });
+            }
+            "REPEAT" => alarm.repeat = value.trim().parse().map_err(|_| invalid())?,
+            "DURATION" => alarm.duration = Some(Duration::parse(value).ok_or_else(invalid)?),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // Properties of a VTIMEZONE and its STANDARD / DAYLIGHT observances
+    fn parse_timezone_line(&mut self, line: &ContentLine) -> Result<(), DiagnosticKind> {
+        let value = line.value.trim();
+        let invalid = || DiagnosticKind::InvalidValue(value.to_string());
+
+        if let Some(observance) = self.current_observance.as_mut() {
+            match line.name.as_str() {
+                "TZOFFSETFROM" => {
+                    observance.offset_from = Some(parse_utc_offset(value).ok_or_else(invalid)?)
+                }
+                "TZOFFSETTO" => {
+                    observance.offset_to = Some(parse_utc_offset(value).ok_or_else(invalid)?)
+                }
+                "DTSTART" => {
+                    observance.start = Some(parse_datetime(line, &self.timezones)?.naive())
+                }
+                "RRULE" => {
+                    observance.rrule = Some(RecurrenceRule::parse(value).ok_or_else(invalid)?)
                 }
+                "RDATE" => {
+                    let rdates = parse_datetime_list(line, &self.timezones)?;
+                    observance
+                        .rdates
+                        .extend(rdates.iter().map(|rdate| rdate.naive()));
+                }
+                _ => {}
             }
-        } else if time_part.ends_with('Z') {
-            if let Ok(utc_datetime) = DateTime::parse_from_rfc3339(time_part) {
-                return Some(utc_datetime.with_timezone(&Utc));
-            } else {
-                return iso8601::datetime(time_part)
-                    .ok()?
-                    .into_naive()
-                    .map(|naive| naive.and_utc());
+        } else if let Some(timezone) = self.current_timezone.as_mut() {
+            match line.name.as_str() {
+                "TZID" => timezone.tzid = line.value.to_string(),
+                "X-LIC-LOCATION" => timezone.location = Some(line.value.to_string()),
+                _ => {}
             }
+        }
+        Ok(())
+    }
+
+    fn report(&mut self, property: &str, kind: DiagnosticKind) {
+        self.push_diagnostic(Diagnostic {
+            line: self.current_line_number,
+            property: property.to_string(),
+            kind,
+        });
+    }
+
+    fn push_diagnostic(&mut self, diagnostic: Diagnostic) {
+        if self.diagnostics.len() < MAX_DIAGNOSTICS {
+            self.diagnostics.push(diagnostic);
         } else {
-            let naive_datetime = NaiveDateTime::parse_from_str(time_part, "%Y%m%dT%H%M%S").ok()?;
-            // 這裡假設是 UTC，本地時間可以依應用需求改為其他預設時區
-            return Some(naive_datetime.and_utc());
+            self.omitted_diagnostics += 1;
         }
-    } else if datetime_str.ends_with('Z') {
-        if let Ok(utc_datetime) = DateTime::parse_from_rfc3339(datetime_str) {
-            return Some(utc_datetime.with_timezone(&Utc));
+    }
+
+    fn finish_event(&mut self, event: TemporaryEvent) {
+        let Some(start) = &event.start else {
+            return;
+        };
+        if let Some(instance) = self.to_instance(&event, start) {
+            self.push_event(instance);
+        }
+    }
+
+    // The instance of an event starting at `start`, with its alarms
+    fn to_instance(&self, event: &TemporaryEvent, start: &DateTimeValue) -> Option<Event> {
+        let end = event.end_at(start)?;
+        let mut instance = event.to_event(start.to_event_time()?, end.to_event_time()?);
+
+        let (start, end) = (self.to_date_time(start), self.to_date_time(&end));
+        instance.alarms = event
+            .alarms
+            .iter()
+            .flat_map(|alarm| alarm.resolve(&start, &end))
+            .collect();
+        instance.alarms.sort_by_key(|alarm| alarm.time);
+        Some(instance)
+    }
+
+    // Dates start at midnight of the default timezone
+    fn to_date_time(&self, value: &DateTimeValue) -> DateTimeValue {
+        match (value, &self.timezones.floating) {
+            (DateTimeValue::Date(date), Some(zone)) => {
+                DateTimeValue::Zoned(date.and_time(NaiveTime::MIN), zone.clone())
+            }
+            (DateTimeValue::Date(date), None) => {
+                DateTimeValue::Floating(date.and_time(NaiveTime::MIN))
+            }
+            _ => value.clone(),
+        }
+    }
+
+    fn finish_recurring_events(&mut self) {
+        let overrides = std::mem::take(&mut self.overrides);
+        for event in std::mem::take(&mut self.recurring_events) {
+            self.expand_event(&event, &overrides);
+        }
+
+        // Overridden instances replace the generated ones and are complete events on their own
+        for event in overrides {
+            self.finish_event(event);
+        }
+    }
+
+    fn expand_event(&mut self, event: &TemporaryEvent, overrides: &[TemporaryEvent]) {
+        let Some(start) = &event.start else {
+            return;
+        };
+
+        let mut occurrences = match event.rrule.as_ref() {
+            Some(rrule) => self.expand_rrule(event, rrule, start),
+            None => vec![start.clone()],
+        };
+        occurrences.extend(event.rdates.iter().cloned());
+        occurrences.sort_by_key(|occurrence| occurrence.to_utc());
+        occurrences.dedup_by(|a, b| same_instance(a, b));
+
+        for occurrence in occurrences {
+            let excluded = event
+                .exdates
+                .iter()
+                .any(|exdate| same_instance(exdate, &occurrence));
+            let overridden = overrides.iter().any(|instance| {
+                instance.uid == event.uid
+                    && instance
+                        .recurrence_id
+                        .as_ref()
+                        .is_some_and(|recurrence_id| same_instance(recurrence_id, &occurrence))
+            });
+            if excluded || overridden {
+                continue;
+            }
+
+            if let Some(mut instance) = self.to_instance(event, &occurrence) {
+                instance.recurrence_id = Some(instance.start);
+                self.push_event(instance);
+            }
+        }
+    }
+
+    fn expand_rrule(
+        &self,
+        event: &TemporaryEvent,
+        rrule: &RecurrenceRule,
+        start: &DateTimeValue,
+    ) -> Vec<DateTimeValue> {
+        let mut occurrences = Vec::new();
+        for occurrence in rrule.expand(start.clone()) {
+            let Some(occurrence_start) = self.to_date_time(&occurrence).to_utc() else {
+                continue;
+            };
+            // Occurrences are in order, nothing after the window can match
+            if occurrences.len() >= MAX_OCCURRENCES
+                || self
+                    .end_date
+                    .is_some_and(|end_date| occurrence_start > end_date)
+            {
+                break;
+            }
+
+            let Some(occurrence_end) = event
+                .end_at(&occurrence)
+                .and_then(|end| self.to_date_time(&end).to_utc())
+            else {
+                continue;
+            };
+            // Occurrences before the window are skipped, a rule starting years ago has many
+            let in_window = match self.start_date {
+                Some(start_date) => occurrence_end >= start_date,
+                None => true,
+            };
+            if in_window {
+                occurrences.push(occurrence);
+            }
+        }
+        occurrences
+    }
+
+    fn push_event(&mut self, event: Event) {
+        if self.overlaps_window(&event.start, &event.end) {
+            self.sink.push(event);
+        }
+    }
+
+    // Keep events overlapping the window, a missing bound leaves that side open.
+    // Events without length count when they are at or after the window start.
+    fn overlaps_window(&self, start: &EventTime, end: &EventTime) -> bool {
+        let (Some(start), Some(end)) = (self.to_instant(start), self.to_instant(end)) else {
+            return false;
+        };
+        let after_start = match self.start_date {
+            Some(start_date) => end > start_date || start >= start_date,
+            None => true,
+        };
+        let before_end = match self.end_date {
+            Some(end_date) => start < end_date,
+            None => true,
+        };
+        after_start && before_end
+    }
+
+    // Tasks are kept until they are done, overdue or not, unless they begin after the window.
+    // Tasks due before the window are left out, old forgotten ones would come first.
+    fn todo_in_window(&self, todo: &Todo) -> bool {
+        let due_in_window = match (todo.due, self.start_date) {
+            (Some(due), Some(start_date)) => {
+                self.to_instant(&due).is_some_and(|due| due >= start_date)
+            }
+            _ => true,
+        };
+        let begins_in_window = match (todo.start.or(todo.due), self.end_date) {
+            (Some(first), Some(end_date)) => self
+                .to_instant(&first)
+                .is_some_and(|first| first < end_date),
+            _ => true,
+        };
+        due_in_window && begins_in_window
+    }
+
+    // All-day events are placed on the days of the default timezone
+    fn to_instant(&self, time: &EventTime) -> Option<DateTime<Utc>> {
+        match (time, &self.timezones.floating) {
+            (EventTime::Date(date), Some(zone)) => zone.to_utc(&date.and_time(NaiveTime::MIN)),
+            (EventTime::Date(date), None) => Some(date.and_time(NaiveTime::MIN).and_utc()),
+            (EventTime::DateTime(datetime), _) => Some(*datetime),
+        }
+    }
+
+    fn decode_text(&self, value: &str) -> String {
+        let text = text::unescape_text(value);
+        if self.decode_html_entities {
+            text::decode_html_entities(&text)
         } else {
-            return iso8601::datetime(datetime_str)
-                .ok()?
-                .into_naive()
-                .map(|naive| naive.and_utc());
+            text
         }
-    } else {
-        let naive_datetime = NaiveDateTime::parse_from_str(datetime_str, "%Y%m%dT%H%M%S").ok()?;
-        // 這裡假設是 UTC，本地時間可以依應用需求改為其他預設時區
-        return Some(naive_datetime.and_utc());
     }
-    None
+
+    // Known once the calendar properties before the first component have been read
+    pub fn calendar(&self) -> &CalendarInfo {
+        &self.calendar
+    }
+
+    pub fn todos(&self) -> &[Todo] {
+        &self.todos
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    pub fn get_events(mut self) -> S {
+        self.flush();
+        self.sink
+    }
+
+    // All events with the problems found, a strict parser fails on the first problem
+    pub fn finish(mut self) -> Result<ParseOutput<S>, ParseError> {
+        self.flush();
+        self.check_strict()?;
+        Ok(ParseOutput {
+            calendar: self.calendar,
+            events: self.sink,
+            todos: self.todos,
+            diagnostics: self.diagnostics,
+            omitted_diagnostics: self.omitted_diagnostics,
+            skipped_events: self.skipped_events,
+        })
+    }
+}
+
+// e.g. `DTSTART` of `DTSTART;TZID=Asia/Taipei:20241205T100000`, also for malformed lines
+fn property_name(line: &str) -> &str {
+    let end = line.find([';', ':']).unwrap_or(line.len());
+    &line[..end]
 }
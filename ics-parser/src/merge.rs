@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use crate::event::{Event, EventTime};
+
+// An event found in one or more calendars
+#[derive(Debug, Clone)]
+pub struct MergedEvent {
+    pub event: Event,
+    // The calendar `event` came from, its options apply to the merged event
+    pub source: String,
+    // The calendars (e.g. their URLs) the event came from
+    pub sources: Vec<String>,
+}
+
+// Combines the events of several calendars, an event shared by calendars (same UID and
+// RECURRENCE-ID) is kept once, in its most recent version
+#[derive(Debug, Default)]
+pub struct EventMerger {
+    events: Vec<MergedEvent>,
+    indices: HashMap<(String, Option<EventTime>), usize>,
+}
+
+impl EventMerger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, source: &str, events: impl IntoIterator<Item = Event>) {
+        for event in events {
+            self.add_event(source, event);
+        }
+    }
+
+    fn add_event(&mut self, source: &str, event: Event) {
+        // Without a UID there is nothing to compare against
+        if event.uid.is_empty() {
+            self.events.push(MergedEvent {
+                event,
+                source: source.to_string(),
+                sources: vec![source.to_string()],
+            });
+            return;
+        }
+
+        let key = (event.uid.clone(), event.recurrence_id);
+        let Some(&index) = self.indices.get(&key) else {
+            self.indices.insert(key, self.events.len());
+            self.events.push(MergedEvent {
+                event,
+                source: source.to_string(),
+                sources: vec![source.to_string()],
+            });
+            return;
+        };
+
+        let merged = &mut self.events[index];
+        if !merged.sources.iter().any(|existing| existing == source) {
+            merged.sources.push(source.to_string());
+        }
+        if is_newer(&event, &merged.event) {
+            merged.event = event;
+            merged.source = source.to_string();
+        }
+    }
+
+    pub fn finish(self) -> Vec<MergedEvent> {
+        self.events
+    }
+}
+
+// A higher SEQUENCE wins, then the later LAST-MODIFIED
+fn is_newer(event: &Event, other: &Event) -> bool {
+    (event.sequence, event.last_modified) > (other.sequence, other.last_modified)
+}
@@ -0,0 +1,372 @@
+use std::collections::VecDeque;
+
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveTime, Weekday};
+
+use crate::datetime::{parse_datetime_value, DateTimeValue};
+
+// Give up on a rule that has not matched a single day for this many periods in a row
+const MAX_EMPTY_PERIODS: u32 = 3000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+// RFC 5545 3.3.10 recurrence rule, limited to the parts used by common calendar providers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<DateTimeValue>,
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    pub by_month_day: Vec<i32>,
+    pub by_month: Vec<u32>,
+    pub by_set_pos: Vec<i32>,
+    pub week_start: Weekday,
+}
+
+impl RecurrenceRule {
+    // Parse the value of a RRULE property, e.g. `FREQ=MONTHLY;BYDAY=-1FR;COUNT=3`
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut frequency = None;
+        let mut rule = RecurrenceRule {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            by_set_pos: Vec::new(),
+            week_start: Weekday::Mon,
+        };
+
+        for part in value.split(';') {
+            let (key, value) = part.split_once('=')?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        // SECONDLY, MINUTELY and HOURLY are not supported
+                        _ => return None,
+                    })
+                }
+                "INTERVAL" => rule.interval = value.parse().ok().filter(|i| *i > 0)?,
+                "COUNT" => rule.count = Some(value.parse().ok()?),
+                "UNTIL" => rule.until = Some(parse_datetime_value(value)?),
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(parse_weekday_num)
+                        .collect::<Option<_>>()?
+                }
+                "BYMONTHDAY" => rule.by_month_day = parse_list(value, 1..=31)?,
+                "BYMONTH" => {
+                    rule.by_month = parse_list(value, 1..=12)?
+                        .into_iter()
+                        .map(|month| month as u32)
+                        .collect()
+                }
+                "BYSETPOS" => rule.by_set_pos = parse_list(value, 1..=366)?,
+                "WKST" => rule.week_start = parse_weekday(value)?,
+                // Unsupported parts are ignored
+                _ => {}
+            }
+        }
+
+        rule.frequency = frequency?;
+        Some(rule)
+    }
+
+    // Iterate over the occurrences of the rule, `start` always counts as the first one
+    pub fn expand(&self, start: DateTimeValue) -> Expansion {
+        let mut rule = self.clone();
+        let start_date = start.naive().date();
+
+        // RFC 5545 3.3.10: missing BYxxx parts are taken from DTSTART
+        if rule.by_day.is_empty() && rule.by_month_day.is_empty() {
+            match rule.frequency {
+                Frequency::Yearly => {
+                    if rule.by_month.is_empty() {
+                        rule.by_month.push(start_date.month());
+                    }
+                    rule.by_month_day.push(start_date.day() as i32);
+                }
+                Frequency::Monthly => rule.by_month_day.push(start_date.day() as i32),
+                Frequency::Weekly => rule.by_day.push((None, start_date.weekday())),
+                Frequency::Daily => {}
+            }
+        }
+
+        let period = match rule.frequency {
+            Frequency::Daily => start_date,
+            Frequency::Weekly => {
+                let days_from_week_start = (start_date.weekday().num_days_from_monday() + 7
+                    - rule.week_start.num_days_from_monday())
+                    % 7;
+                start_date - Duration::days(days_from_week_start as i64)
+            }
+            Frequency::Monthly => start_date.with_day(1).unwrap(),
+            Frequency::Yearly => start_date.with_ordinal(1).unwrap(),
+        };
+
+        Expansion {
+            rule,
+            time: start.naive().time(),
+            start,
+            period: Some(period),
+            pending: VecDeque::new(),
+            started: false,
+            emitted: 0,
+            empty_periods: 0,
+        }
+    }
+}
+
+pub struct Expansion {
+    rule: RecurrenceRule,
+    start: DateTimeValue,
+    time: NaiveTime,
+    period: Option<NaiveDate>,
+    pending: VecDeque<NaiveDate>,
+    started: bool,
+    emitted: u32,
+    empty_periods: u32,
+}
+
+impl Expansion {
+    // Fill `pending` with the matching days of the current period, then move to the next one
+    fn next_period(&mut self) {
+        let Some(period) = self.period else {
+            return;
+        };
+
+        let period_end = match self.rule.frequency {
+            Frequency::Daily => period.checked_add_signed(Duration::days(1)),
+            Frequency::Weekly => period.checked_add_signed(Duration::days(7)),
+            Frequency::Monthly => period.checked_add_months(Months::new(1)),
+            Frequency::Yearly => period.checked_add_months(Months::new(12)),
+        };
+        // The last period of the range of dates
+        let Some(period_end) = period_end else {
+            self.period = None;
+            return;
+        };
+
+        let mut days = period
+            .iter_days()
+            .take_while(|day| *day < period_end)
+            .filter(|day| self.matches(*day))
+            .collect::<Vec<_>>();
+
+        if !self.rule.by_set_pos.is_empty() {
+            let mut selected = self
+                .rule
+                .by_set_pos
+                .iter()
+                .filter_map(|pos| {
+                    let index = if *pos > 0 {
+                        *pos - 1
+                    } else {
+                        days.len() as i32 + *pos
+                    };
+                    days.get(usize::try_from(index).ok()?).copied()
+                })
+                .collect::<Vec<_>>();
+            selected.sort();
+            selected.dedup();
+            days = selected;
+        }
+
+        if days.is_empty() {
+            self.empty_periods += 1;
+        } else {
+            self.empty_periods = 0;
+        }
+
+        self.pending.extend(days);
+
+        let interval = self.rule.interval;
+        self.period = if self.empty_periods >= MAX_EMPTY_PERIODS {
+            None
+        } else {
+            match self.rule.frequency {
+                Frequency::Daily => period.checked_add_signed(Duration::days(interval as i64)),
+                Frequency::Weekly => period.checked_add_signed(Duration::days(7 * interval as i64)),
+                Frequency::Monthly => period.checked_add_months(Months::new(interval)),
+                Frequency::Yearly => 12_u32
+                    .checked_mul(interval)
+                    .and_then(|months| period.checked_add_months(Months::new(months))),
+            }
+        };
+    }
+
+    fn matches(&self, day: NaiveDate) -> bool {
+        let rule = &self.rule;
+
+        if !rule.by_month.is_empty() && !rule.by_month.contains(&day.month()) {
+            return false;
+        }
+
+        let days_in_month = days_in_month(day);
+        if !rule.by_month_day.is_empty()
+            && !rule.by_month_day.iter().any(|month_day| {
+                *month_day == day.day() as i32
+                    || *month_day == day.day() as i32 - days_in_month as i32 - 1
+            })
+        {
+            return false;
+        }
+
+        if !rule.by_day.is_empty() {
+            // Ordinals count within the month, or within the year for a plain yearly rule
+            let (index, length) = match rule.frequency {
+                Frequency::Monthly => (day.day(), days_in_month),
+                Frequency::Yearly if !rule.by_month.is_empty() => (day.day(), days_in_month),
+                Frequency::Yearly => (day.ordinal(), days_in_year(day)),
+                // Ordinals are meaningless for daily and weekly rules
+                _ => (0, 0),
+            };
+
+            let matched = rule.by_day.iter().any(|(ordinal, weekday)| {
+                if *weekday != day.weekday() {
+                    return false;
+                }
+                match ordinal {
+                    Some(ordinal) if length > 0 => {
+                        let from_start = ((index - 1) / 7 + 1) as i32;
+                        let from_end = -(((length - index) / 7 + 1) as i32);
+                        *ordinal == from_start || *ordinal == from_end
+                    }
+                    _ => true,
+                }
+            });
+
+            if !matched {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl Iterator for Expansion {
+    type Item = DateTimeValue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            if self.rule.count != Some(0) {
+                self.emitted += 1;
+                return Some(self.start.clone());
+            }
+        }
+
+        loop {
+            if let Some(day) = self.pending.pop_front() {
+                let naive = day.and_time(self.time);
+                if naive <= self.start.naive() {
+                    continue;
+                }
+
+                if let Some(count) = self.rule.count {
+                    if self.emitted >= count {
+                        self.period = None;
+                        self.pending.clear();
+                        return None;
+                    }
+                }
+
+                let occurrence = self.start.with_naive(naive);
+                if let Some(until) = &self.rule.until {
+                    if is_after(&occurrence, until) {
+                        self.period = None;
+                        self.pending.clear();
+                        return None;
+                    }
+                }
+
+                self.emitted += 1;
+                return Some(occurrence);
+            }
+
+            self.period?;
+            self.next_period();
+        }
+    }
+}
+
+fn is_after(occurrence: &DateTimeValue, until: &DateTimeValue) -> bool {
+    match (occurrence, until) {
+        // A date-only UNTIL includes the whole day
+        (_, DateTimeValue::Date(until)) => occurrence.naive().date() > *until,
+        (DateTimeValue::Date(date), _) => *date > until.naive().date(),
+        (_, DateTimeValue::Utc(_)) => match (occurrence.to_utc(), until.to_utc()) {
+            (Some(occurrence), Some(until)) => occurrence > until,
+            _ => false,
+        },
+        _ => occurrence.naive() > until.naive(),
+    }
+}
+
+fn days_in_month(day: NaiveDate) -> u32 {
+    let first = day.with_day(1).unwrap();
+    ((first + Months::new(1)) - first).num_days() as u32
+}
+
+fn days_in_year(day: NaiveDate) -> u32 {
+    if NaiveDate::from_ymd_opt(day.year(), 2, 29).is_some() {
+        366
+    } else {
+        365
+    }
+}
+
+fn parse_list(value: &str, range: std::ops::RangeInclusive<i32>) -> Option<Vec<i32>> {
+    value
+        .split(',')
+        .map(|item| {
+            let number: i32 = item.trim().parse().ok()?;
+            range.contains(&number.abs()).then_some(number)
+        })
+        .collect()
+}
+
+// e.g. `MO`, `2MO`, `-1FR`
+fn parse_weekday_num(value: &str) -> Option<(Option<i32>, Weekday)> {
+    let value = value.trim();
+    let split = value.len().checked_sub(2)?;
+    let weekday = parse_weekday(value.get(split..)?)?;
+    let ordinal = match &value[..split] {
+        "" => None,
+        ordinal => Some(
+            ordinal
+                .trim_start_matches('+')
+                .parse()
+                .ok()
+                .filter(|o: &i32| (1..=53).contains(&o.abs()))?,
+        ),
+    };
+    Some((ordinal, weekday))
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    match value.to_ascii_uppercase().as_str() {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
@@ -0,0 +1,90 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use crate::event::Event;
+
+// Receives the events of a feed one by one, while it is being parsed
+pub trait EventSink {
+    fn push(&mut self, event: Event);
+}
+
+impl EventSink for Vec<Event> {
+    fn push(&mut self, event: Event) {
+        Vec::push(self, event);
+    }
+}
+
+impl<F: FnMut(Event)> EventSink for F {
+    fn push(&mut self, event: Event) {
+        self(event);
+    }
+}
+
+// Keeps the `capacity` earliest events, memory does not grow with the size of the feed
+#[derive(Debug, Clone)]
+pub struct EarliestEvents {
+    capacity: usize,
+    // The latest kept event is on top, it is the first to go
+    heap: BinaryHeap<ByStart>,
+}
+
+// Sorts by the start alone, whichever way `Event` itself sorts
+#[derive(Debug, Clone)]
+struct ByStart(Event);
+
+impl PartialEq for ByStart {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.start == other.0.start
+    }
+}
+
+impl Eq for ByStart {}
+
+impl PartialOrd for ByStart {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ByStart {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.start.cmp(&other.0.start)
+    }
+}
+
+impl EarliestEvents {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            heap: BinaryHeap::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    // From the earliest to the latest
+    pub fn into_sorted_vec(self) -> Vec<Event> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|ByStart(event)| event)
+            .collect()
+    }
+}
+
+impl EventSink for EarliestEvents {
+    fn push(&mut self, event: Event) {
+        if self.heap.len() < self.capacity {
+            self.heap.push(ByStart(event));
+        } else if let Some(mut latest) = self.heap.peek_mut() {
+            if event.start < latest.0.start {
+                *latest = ByStart(event);
+            }
+        }
+    }
+}
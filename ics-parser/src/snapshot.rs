@@ -0,0 +1,334 @@
+use std::{error::Error, fmt};
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+
+use crate::{
+    alarm::{Alarm, AlarmAction},
+    event::{Event, EventTime},
+    todo::{Todo, TodoStatus},
+};
+
+const MAGIC: &[u8; 4] = b"SDCS";
+
+// Bumped whenever the layout changes, older snapshots are then rejected
+pub const SNAPSHOT_VERSION: u8 = 1;
+
+// The events and open tasks on display, kept between wake-ups so there is something to
+// draw when the network is down. Only what the device shows is kept: the UID, summary,
+// location, times and alarms of events, and the UID, summary, times, priority and status
+// of tasks.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub created: DateTime<Utc>,
+    pub events: Vec<Event>,
+    pub todos: Vec<Todo>,
+}
+
+// Why a snapshot could not be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    // Not a snapshot at all
+    BadMagic,
+    UnsupportedVersion(u8),
+    // The data was damaged, e.g. by an interrupted write
+    ChecksumMismatch,
+    // The data ends early or has values out of range
+    Malformed,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::BadMagic => write!(f, "not a snapshot"),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot version {}", version)
+            }
+            SnapshotError::ChecksumMismatch => write!(f, "snapshot checksum mismatch"),
+            SnapshotError::Malformed => write!(f, "malformed snapshot"),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+// Layout: magic, version, payload, CRC-32 of everything before it (little endian).
+// Integers in the payload are LEB128 varints, signed ones zigzag encoded.
+impl Snapshot {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Writer(Vec::new());
+        out.0.extend_from_slice(MAGIC);
+        out.0.push(SNAPSHOT_VERSION);
+
+        out.signed(self.created.timestamp());
+        out.unsigned(self.events.len() as u64);
+        for event in &self.events {
+            out.str(&event.uid);
+            out.str(&event.summary);
+            out.optional_str(event.location.as_deref());
+            out.time(&event.start);
+            out.time(&event.end);
+            out.unsigned(event.alarms.len() as u64);
+            for alarm in &event.alarms {
+                out.signed(alarm.time.timestamp());
+                match &alarm.action {
+                    AlarmAction::Display => out.0.push(0),
+                    AlarmAction::Audio => out.0.push(1),
+                    AlarmAction::Email => out.0.push(2),
+                    AlarmAction::Other(action) => {
+                        out.0.push(3);
+                        out.str(action);
+                    }
+                }
+            }
+        }
+
+        out.unsigned(self.todos.len() as u64);
+        for todo in &self.todos {
+            out.str(&todo.uid);
+            out.str(&todo.summary);
+            out.optional_time(todo.start.as_ref());
+            out.optional_time(todo.due.as_ref());
+            out.0.push(todo.priority);
+            out.0.push(match todo.status {
+                None => 0,
+                Some(TodoStatus::NeedsAction) => 1,
+                Some(TodoStatus::InProcess) => 2,
+                Some(TodoStatus::Completed) => 3,
+                Some(TodoStatus::Cancelled) => 4,
+            });
+        }
+
+        let checksum = crc32(&out.0);
+        out.0.extend_from_slice(&checksum.to_le_bytes());
+        out.0
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let Some((&version, _)) = bytes[MAGIC.len()..].split_first() else {
+            return Err(SnapshotError::Malformed);
+        };
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        if bytes.len() < MAGIC.len() + 1 + 4 {
+            return Err(SnapshotError::Malformed);
+        }
+        let (data, checksum) = bytes.split_at(bytes.len() - 4);
+        if crc32(data).to_le_bytes() != checksum {
+            return Err(SnapshotError::ChecksumMismatch);
+        }
+
+        let mut input = Reader(&data[MAGIC.len() + 1..]);
+        let created =
+            DateTime::from_timestamp(input.signed()?, 0).ok_or(SnapshotError::Malformed)?;
+
+        let count = input.count()?;
+        let mut events = Vec::with_capacity(count);
+        for _ in 0..count {
+            let uid = input.str()?;
+            let summary = input.str()?;
+            let location = input.optional_str()?;
+            let start = input.time()?;
+            let end = input.time()?;
+            let mut event = Event::new(&summary, start, end);
+            event.uid = uid;
+            event.location = location;
+
+            let count = input.count()?;
+            for _ in 0..count {
+                let time =
+                    DateTime::from_timestamp(input.signed()?, 0).ok_or(SnapshotError::Malformed)?;
+                let action = match input.byte()? {
+                    0 => AlarmAction::Display,
+                    1 => AlarmAction::Audio,
+                    2 => AlarmAction::Email,
+                    3 => AlarmAction::Other(input.str()?),
+                    _ => return Err(SnapshotError::Malformed),
+                };
+                event.alarms.push(Alarm {
+                    action,
+                    description: None,
+                    time,
+                });
+            }
+            events.push(event);
+        }
+
+        let count = input.count()?;
+        let mut todos = Vec::with_capacity(count);
+        for _ in 0..count {
+            todos.push(Todo {
+                uid: input.str()?,
+                summary: input.str()?,
+                start: input.optional_time()?,
+                due: input.optional_time()?,
+                priority: input.byte()?,
+                status: match input.byte()? {
+                    0 => None,
+                    1 => Some(TodoStatus::NeedsAction),
+                    2 => Some(TodoStatus::InProcess),
+                    3 => Some(TodoStatus::Completed),
+                    4 => Some(TodoStatus::Cancelled),
+                    _ => return Err(SnapshotError::Malformed),
+                },
+                ..Todo::default()
+            });
+        }
+
+        if !input.0.is_empty() {
+            return Err(SnapshotError::Malformed);
+        }
+        Ok(Self {
+            created,
+            events,
+            todos,
+        })
+    }
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn unsigned(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    fn signed(&mut self, value: i64) {
+        self.unsigned(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    fn str(&mut self, value: &str) {
+        self.unsigned(value.len() as u64);
+        self.0.extend_from_slice(value.as_bytes());
+    }
+
+    fn optional_str(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                self.0.push(1);
+                self.str(value);
+            }
+            None => self.0.push(0),
+        }
+    }
+
+    // Dates as days since 0001-01-01, date-times as seconds since 1970
+    fn time(&mut self, time: &EventTime) {
+        match time {
+            EventTime::Date(date) => {
+                self.0.push(0);
+                self.signed(i64::from(date.num_days_from_ce()));
+            }
+            EventTime::DateTime(datetime) => {
+                self.0.push(1);
+                self.signed(datetime.timestamp());
+            }
+        }
+    }
+
+    fn optional_time(&mut self, time: Option<&EventTime>) {
+        match time {
+            Some(time) => self.time(time),
+            None => self.0.push(2),
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, SnapshotError> {
+        let (&byte, rest) = self.0.split_first().ok_or(SnapshotError::Malformed)?;
+        self.0 = rest;
+        Ok(byte)
+    }
+
+    fn unsigned(&mut self) -> Result<u64, SnapshotError> {
+        let mut value = 0_u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(SnapshotError::Malformed)
+    }
+
+    fn signed(&mut self) -> Result<i64, SnapshotError> {
+        let value = self.unsigned()?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    // A number of items, each takes at least a byte so a damaged count cannot
+    // make the decoder allocate more than the input
+    fn count(&mut self) -> Result<usize, SnapshotError> {
+        let count = self.unsigned()?;
+        if count > self.0.len() as u64 {
+            return Err(SnapshotError::Malformed);
+        }
+        Ok(count as usize)
+    }
+
+    fn str(&mut self) -> Result<String, SnapshotError> {
+        let len = self.count()?;
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        String::from_utf8(bytes.to_vec()).map_err(|_| SnapshotError::Malformed)
+    }
+
+    fn optional_str(&mut self) -> Result<Option<String>, SnapshotError> {
+        match self.byte()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.str()?)),
+            _ => Err(SnapshotError::Malformed),
+        }
+    }
+
+    fn time(&mut self) -> Result<EventTime, SnapshotError> {
+        match self.optional_time()? {
+            Some(time) => Ok(time),
+            None => Err(SnapshotError::Malformed),
+        }
+    }
+
+    fn optional_time(&mut self) -> Result<Option<EventTime>, SnapshotError> {
+        let time = match self.byte()? {
+            0 => {
+                let days = i32::try_from(self.signed()?).map_err(|_| SnapshotError::Malformed)?;
+                EventTime::Date(
+                    NaiveDate::from_num_days_from_ce_opt(days).ok_or(SnapshotError::Malformed)?,
+                )
+            }
+            1 => EventTime::DateTime(
+                DateTime::from_timestamp(self.signed()?, 0).ok_or(SnapshotError::Malformed)?,
+            ),
+            2 => return Ok(None),
+            _ => return Err(SnapshotError::Malformed),
+        };
+        Ok(Some(time))
+    }
+}
+
+// CRC-32 as used by zip and PNG
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
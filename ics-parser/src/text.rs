@@ -0,0 +1,131 @@
+// RFC 5545 3.3.11: TEXT values escape backslashes, semicolons, commas and newlines
+pub fn unescape_text(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(escaped @ ('\\' | ';' | ',')) => result.push(escaped),
+            // Not a valid escape sequence, keep it as it is
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+// The reverse of `unescape_text`, line breaks become `\n`
+pub fn escape_text(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' | ';' | ',' => {
+                result.push('\\');
+                result.push(c);
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => result.push_str("\\n"),
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+// A parameter value as written in a content line. Values cannot hold quotes at all,
+// they are quoted when they contain a separator.
+pub fn quote_parameter(value: &str) -> String {
+    let value = value.replace('"', "'");
+    if value.contains([':', ';', ',']) {
+        format!("\"{}\"", value)
+    } else {
+        value
+    }
+}
+
+// Split a list of TEXT values (e.g. CATEGORIES) on the commas that are not escaped,
+// the values are still escaped
+pub fn split_text_list(value: &str) -> Vec<&str> {
+    let mut values = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+
+    for (index, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ',' => {
+                values.push(&value[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    values.push(&value[start..]);
+    values
+}
+
+// Some providers (e.g. Moodle) put HTML-escaped text into their feeds
+pub fn decode_html_entities(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // Entities are short, do not scan the whole string for a semicolon
+        let decoded = rest
+            .char_indices()
+            .take(12)
+            .find(|(_, c)| *c == ';')
+            .and_then(|(end, _)| decode_entity(&rest[1..end]).map(|c| (c, end)));
+
+        match decoded {
+            Some((c, end)) => {
+                result.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => None,
+    }
+}
@@ -0,0 +1,203 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::{
+    datetime::DateTimeValue,
+    rrule::{Frequency, RecurrenceRule},
+    windows_zones::windows_zone,
+};
+
+// The timezone a TZID parameter refers to
+#[derive(Debug, Clone)]
+pub enum Zone {
+    Iana(Tz),
+    // Defined by a VTIMEZONE block of the feed
+    Custom(Arc<CustomZone>),
+}
+
+impl PartialEq for Zone {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Zone::Iana(a), Zone::Iana(b)) => a == b,
+            (Zone::Custom(a), Zone::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Zone {}
+
+impl Zone {
+    pub fn to_utc(&self, naive: &NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            Zone::Iana(tz) => resolve_local(tz, naive),
+            Zone::Custom(zone) => {
+                Some((*naive - Duration::seconds(zone.offset_at(naive))).and_utc())
+            }
+        }
+    }
+}
+
+// Resolve a wall clock time, preferring the earlier instant when the clock goes back
+// and moving past the gap when the clock goes forward
+fn resolve_local(tz: &Tz, naive: &NaiveDateTime) -> Option<DateTime<Utc>> {
+    match tz.from_local_datetime(naive) {
+        LocalResult::Single(datetime) => Some(datetime.to_utc()),
+        LocalResult::Ambiguous(earliest, _) => Some(earliest.to_utc()),
+        LocalResult::None => tz
+            .from_local_datetime(&(*naive + Duration::hours(1)))
+            .earliest()
+            .map(|datetime| datetime.to_utc()),
+    }
+}
+
+// A VTIMEZONE block
+#[derive(Debug, Clone, Default)]
+pub struct CustomZone {
+    pub tzid: String,
+    pub location: Option<String>,
+    pub observances: Vec<Observance>,
+}
+
+// A STANDARD or DAYLIGHT block of a VTIMEZONE
+#[derive(Debug, Clone, Default)]
+pub struct Observance {
+    pub start: Option<NaiveDateTime>,
+    pub offset_from: Option<i64>,
+    pub offset_to: Option<i64>,
+    pub rrule: Option<RecurrenceRule>,
+    pub rdates: Vec<NaiveDateTime>,
+}
+
+impl CustomZone {
+    // UTC offset in seconds of a wall clock time
+    fn offset_at(&self, naive: &NaiveDateTime) -> i64 {
+        let latest = self
+            .observances
+            .iter()
+            .filter_map(|observance| Some((observance.latest_onset(naive)?, observance)))
+            .max_by_key(|(onset, _)| *onset);
+
+        match latest {
+            Some((_, observance)) => observance.offset_to.unwrap_or(0),
+            // Before the first transition, use the offset the earliest one moves away from
+            None => self
+                .observances
+                .iter()
+                .filter(|observance| observance.start.is_some())
+                .min_by_key(|observance| observance.start)
+                .and_then(|observance| observance.offset_from.or(observance.offset_to))
+                .unwrap_or(0),
+        }
+    }
+}
+
+impl Observance {
+    // A rule matching no day, e.g. `BYMONTH=2;BYMONTHDAY=30`, would be searched to the end
+    // on every conversion. Without it the observance only takes effect at DTSTART and RDATEs.
+    pub fn drop_empty_rule(&mut self) {
+        let (Some(start), Some(rrule)) = (self.start, &self.rrule) else {
+            return;
+        };
+        if rrule
+            .expand(DateTimeValue::Floating(start))
+            .nth(1)
+            .is_none()
+        {
+            self.rrule = None;
+        }
+    }
+
+    // The last time this observance took effect at or before a wall clock time
+    fn latest_onset(&self, naive: &NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = self.start?;
+        if start > *naive {
+            return None;
+        }
+
+        let mut latest = start;
+        if let Some(rrule) = &self.rrule {
+            // Yearly rules without COUNT do not depend on the year of DTSTART, skip ahead
+            // instead of expanding every transition since 1970
+            let from = match rrule.count {
+                None if rrule.frequency == Frequency::Yearly && start.year() < naive.year() - 1 => {
+                    start.with_year(naive.year() - 1).unwrap_or(start)
+                }
+                _ => start,
+            };
+
+            for onset in rrule.expand(DateTimeValue::Floating(from)) {
+                let onset = onset.naive();
+                if onset > *naive {
+                    break;
+                }
+                latest = latest.max(onset);
+            }
+        }
+
+        for rdate in &self.rdates {
+            if rdate <= naive {
+                latest = latest.max(*rdate);
+            }
+        }
+
+        Some(latest)
+    }
+}
+
+// The timezones known while parsing a feed
+#[derive(Debug, Clone, Default)]
+pub struct TimeZones {
+    custom: HashMap<String, Arc<CustomZone>>,
+    // Floating times (no `Z` and no TZID) are resolved in this zone
+    pub floating: Option<Zone>,
+}
+
+impl TimeZones {
+    pub fn insert(&mut self, zone: CustomZone) {
+        self.custom.insert(zone.tzid.clone(), Arc::new(zone));
+    }
+
+    // IANA names first, then Windows names (Outlook / Exchange), then VTIMEZONE definitions
+    pub fn resolve(&self, tzid: &str) -> Option<Zone> {
+        if let Some(tz) = parse_iana(tzid).or_else(|| windows_zone(tzid)) {
+            return Some(Zone::Iana(tz));
+        }
+
+        let custom = self.custom.get(tzid)?;
+        match custom.location.as_deref().and_then(parse_iana) {
+            Some(tz) => Some(Zone::Iana(tz)),
+            None => Some(Zone::Custom(custom.clone())),
+        }
+    }
+}
+
+// Also accepts prefixed names such as `/mozilla.org/20050126_1/America/New_York`
+fn parse_iana(tzid: &str) -> Option<Tz> {
+    if let Ok(tz) = tzid.parse() {
+        return Some(tz);
+    }
+
+    tzid.match_indices('/')
+        .find_map(|(index, _)| tzid[index + 1..].parse().ok())
+}
+
+// e.g. `+0800`, `-0330`, `+053000`
+pub fn parse_utc_offset(value: &str) -> Option<i64> {
+    let (sign, digits) = match value.as_bytes().first()? {
+        b'+' => (1, &value[1..]),
+        b'-' => (-1, &value[1..]),
+        _ => return None,
+    };
+
+    if !(digits.len() == 4 || digits.len() == 6) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let hours: i64 = digits[0..2].parse().ok()?;
+    let minutes: i64 = digits[2..4].parse().ok()?;
+    let seconds: i64 = digits.get(4..6).map_or(Ok(0), str::parse).ok()?;
+    Some(sign * (hours * 3600 + minutes * 60 + seconds))
+}
@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+
+use crate::event::EventTime;
+
+// STATUS of a VTODO
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TodoStatus {
+    NeedsAction,
+    InProcess,
+    Completed,
+    Cancelled,
+}
+
+impl TodoStatus {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "NEEDS-ACTION" => Some(TodoStatus::NeedsAction),
+            "IN-PROCESS" => Some(TodoStatus::InProcess),
+            "COMPLETED" => Some(TodoStatus::Completed),
+            "CANCELLED" => Some(TodoStatus::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+// A VTODO, a task that may have a due date
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Todo {
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub start: Option<EventTime>,
+    pub due: Option<EventTime>,
+    // When the task was done
+    pub completed: Option<DateTime<Utc>>,
+    // 0 to 100
+    pub percent_complete: Option<u8>,
+    // 1 is the highest, 9 the lowest, 0 is undefined
+    pub priority: u8,
+    pub status: Option<TodoStatus>,
+}
+
+impl Todo {
+    // Apps mark a task as done in different ways, any of them counts
+    pub fn is_completed(&self) -> bool {
+        self.status == Some(TodoStatus::Completed)
+            || self.completed.is_some()
+            || self.percent_complete == Some(100)
+    }
+
+    // Still to be done: neither completed nor cancelled
+    pub fn is_open(&self) -> bool {
+        !self.is_completed() && self.status != Some(TodoStatus::Cancelled)
+    }
+}
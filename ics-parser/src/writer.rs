@@ -0,0 +1,203 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    calendar::CalendarInfo,
+    event::{Event, EventTime},
+    text::{escape_text, quote_parameter},
+};
+
+// RFC 5545 3.1: lines longer than this are folded
+const MAX_LINE_OCTETS: usize = 75;
+
+// Used when the calendar does not say which application wrote it
+const DEFAULT_PRODUCT_ID: &str = "-//smart-desk-calendar//ics-parser//EN";
+
+// Writes events back as an RFC 5545 feed that `IcsParser` and calendar apps can read.
+// Date-times are written in UTC, recurring events as their separate instances.
+pub struct IcsWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> IcsWriter<W> {
+    // Starts the VCALENDAR with the calendar properties
+    pub fn new(out: W, calendar: &CalendarInfo) -> io::Result<Self> {
+        let mut writer = Self { out };
+        writer.property("BEGIN", &[], "VCALENDAR")?;
+        writer.property("VERSION", &[], "2.0")?;
+        writer.property(
+            "PRODID",
+            &[],
+            calendar.product_id.as_deref().unwrap_or(DEFAULT_PRODUCT_ID),
+        )?;
+        writer.optional("METHOD", calendar.method.as_deref())?;
+        writer.optional_text("X-WR-CALNAME", calendar.name.as_deref())?;
+        writer.optional_text("X-WR-CALDESC", calendar.description.as_deref())?;
+        writer.optional("X-APPLE-CALENDAR-COLOR", calendar.color.as_deref())?;
+        writer.optional("X-WR-TIMEZONE", calendar.timezone.as_deref())?;
+        Ok(writer)
+    }
+
+    pub fn write_event(&mut self, event: &Event) -> io::Result<()> {
+        self.property("BEGIN", &[], "VEVENT")?;
+        if !event.uid.is_empty() {
+            self.property("UID", &[], &event.uid)?;
+        }
+        // Required, the time the event was last changed is the closest we know
+        let stamp = event
+            .last_modified
+            .unwrap_or_else(|| event.start.to_utc(&chrono_tz::UTC));
+        self.property("DTSTAMP", &[], &format_utc(&stamp))?;
+        self.time("DTSTART", &event.start)?;
+        self.time("DTEND", &event.end)?;
+        if let Some(recurrence_id) = &event.recurrence_id {
+            self.time("RECURRENCE-ID", recurrence_id)?;
+        }
+        self.property("SUMMARY", &[], &escape_text(&event.summary))?;
+        self.optional_text("DESCRIPTION", event.description.as_deref())?;
+        self.optional_text("LOCATION", event.location.as_deref())?;
+        if !event.categories.is_empty() {
+            let categories = event
+                .categories
+                .iter()
+                .map(|category| escape_text(category))
+                .collect::<Vec<_>>();
+            self.property("CATEGORIES", &[], &categories.join(","))?;
+        }
+        self.optional(
+            "STATUS",
+            event.status.as_ref().map(|status| status.as_str()),
+        )?;
+        self.optional(
+            "TRANSP",
+            event
+                .transparency
+                .as_ref()
+                .map(|transparency| transparency.as_str()),
+        )?;
+        self.optional("CLASS", event.class.as_ref().map(|class| class.as_str()))?;
+        self.optional("URL", event.url.as_deref())?;
+        self.optional("ORGANIZER", event.organizer.as_deref())?;
+        for attendee in &event.attendees {
+            let mut parameters = Vec::new();
+            if let Some(common_name) = &attendee.common_name {
+                parameters.push(("CN", common_name.as_str()));
+            }
+            parameters.push(("PARTSTAT", attendee.participation_status.as_str()));
+            self.property("ATTENDEE", &parameters, &attendee.address)?;
+        }
+        if event.sequence > 0 {
+            self.property("SEQUENCE", &[], &event.sequence.to_string())?;
+        }
+        if let Some(last_modified) = &event.last_modified {
+            self.property("LAST-MODIFIED", &[], &format_utc(last_modified))?;
+        }
+
+        // In name order, so the same event is always written the same way
+        let mut x_properties = event.x_properties.iter().collect::<Vec<_>>();
+        x_properties.sort();
+        for (name, value) in x_properties {
+            self.property(name, &[], &escape_text(value))?;
+        }
+
+        for alarm in &event.alarms {
+            self.property("BEGIN", &[], "VALARM")?;
+            self.property("ACTION", &[], alarm.action.as_str())?;
+            self.property(
+                "TRIGGER",
+                &[("VALUE", "DATE-TIME")],
+                &format_utc(&alarm.time),
+            )?;
+            // Required for display alarms
+            let description = alarm.description.as_deref().unwrap_or(&event.summary);
+            self.property("DESCRIPTION", &[], &escape_text(description))?;
+            self.property("END", &[], "VALARM")?;
+        }
+
+        self.property("END", &[], "VEVENT")
+    }
+
+    // Ends the VCALENDAR
+    pub fn finish(mut self) -> io::Result<W> {
+        self.property("END", &[], "VCALENDAR")?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn time(&mut self, name: &str, time: &EventTime) -> io::Result<()> {
+        match time {
+            EventTime::Date(date) => self.property(
+                name,
+                &[("VALUE", "DATE")],
+                &date.format("%Y%m%d").to_string(),
+            ),
+            EventTime::DateTime(datetime) => self.property(name, &[], &format_utc(datetime)),
+        }
+    }
+
+    fn optional(&mut self, name: &str, value: Option<&str>) -> io::Result<()> {
+        match value {
+            Some(value) => self.property(name, &[], value),
+            None => Ok(()),
+        }
+    }
+
+    fn optional_text(&mut self, name: &str, value: Option<&str>) -> io::Result<()> {
+        match value {
+            Some(value) => self.property(name, &[], &escape_text(value)),
+            None => Ok(()),
+        }
+    }
+
+    // `value` must already be escaped if it is TEXT
+    fn property(&mut self, name: &str, parameters: &[(&str, &str)], value: &str) -> io::Result<()> {
+        let mut line = name.to_string();
+        for (name, value) in parameters {
+            line.push(';');
+            line.push_str(name);
+            line.push('=');
+            line.push_str(&quote_parameter(value));
+        }
+        line.push(':');
+        line.push_str(value);
+        self.write_folded(&line)
+    }
+
+    // Continuation lines start with a space, which counts towards their length.
+    // Lines are only split between characters.
+    fn write_folded(&mut self, line: &str) -> io::Result<()> {
+        let mut rest = line;
+        let mut limit = MAX_LINE_OCTETS;
+        while rest.len() > limit {
+            let mut end = limit;
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            let (head, tail) = rest.split_at(end);
+            self.out.write_all(head.as_bytes())?;
+            self.out.write_all(b"\r\n ")?;
+            rest = tail;
+            limit = MAX_LINE_OCTETS - 1;
+        }
+        self.out.write_all(rest.as_bytes())?;
+        self.out.write_all(b"\r\n")
+    }
+}
+
+fn format_utc(datetime: &DateTime<Utc>) -> String {
+    datetime.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// A whole feed as text
+pub fn to_ics_string(calendar: &CalendarInfo, events: &[Event]) -> String {
+    let write = || -> io::Result<Vec<u8>> {
+        let mut writer = IcsWriter::new(Vec::new(), calendar)?;
+        for event in events {
+            writer.write_event(event)?;
+        }
+        writer.finish()
+    };
+    let bytes = write().expect("writing to a Vec cannot fail");
+    String::from_utf8(bytes).expect("only whole strings are written")
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use chrono::{DateTime, TimeZone, Utc};
+use common::parse;
+use ics_parser::{Alarm, AlarmAction, IcsParser};
+
+fn utc(d: u32, h: u32, min: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 12, d, h, min, 0).unwrap()
+}
+
+fn times(alarms: &[Alarm]) -> Vec<DateTime<Utc>> {
+    alarms.iter().map(|alarm| alarm.time).collect()
+}
+
+#[test]
+fn relative_and_absolute_triggers() {
+    let events = parse(
+        "BEGIN:VEVENT\r\n\
+         SUMMARY:Meeting\r\n\
+         DTSTART:20241205T100000Z\r\n\
+         DTEND:20241205T110000Z\r\n\
+         BEGIN:VALARM\r\n\
+         ACTION:DISPLAY\r\n\
+         DESCRIPTION:Meeting soon\r\n\
+         TRIGGER:-PT15M\r\n\
+         END:VALARM\r\n\
+         BEGIN:VALARM\r\n\
+         ACTION:AUDIO\r\n\
+         TRIGGER;RELATED=END:-PT5M\r\n\
+         END:VALARM\r\n\
+         BEGIN:VALARM\r\n\
+         ACTION:EMAIL\r\n\
+         TRIGGER;VALUE=DATE-TIME:20241204T080000Z\r\n\
+         END:VALARM\r\n\
+         END:VEVENT\r\n",
+    );
+    let alarms = &events[0].alarms;
+    assert_eq!(
+        times(alarms),
+        vec![utc(4, 8, 0), utc(5, 9, 45), utc(5, 10, 55)]
+    );
+    assert_eq!(alarms[0].action, AlarmAction::Email);
+    assert_eq!(alarms[1].action, AlarmAction::Display);
+    assert_eq!(alarms[1].description.as_deref(), Some("Meeting soon"));
+    assert_eq!(alarms[2].action, AlarmAction::Audio);
+}
+
+#[test]
+fn repeated_alarms() {
+    let events = parse(
+        "BEGIN:VEVENT\r\n\
+         DTSTART:20241205T100000Z\r\n\
+         BEGIN:VALARM\r\n\
+         TRIGGER:-PT30M\r\n\
+         REPEAT:2\r\n\
+         DURATION:PT10M\r\n\
+         END:VALARM\r\n\
+         END:VEVENT\r\n",
+    );
+    assert_eq!(
+        times(&events[0].alarms),
+        vec![utc(5, 9, 30), utc(5, 9, 40), utc(5, 9, 50)]
+    );
+    // Without ACTION the alarm is shown
+    assert_eq!(events[0].alarms[0].action, AlarmAction::Display);
+    assert_eq!(
+        events[0].next_alarm(utc(5, 9, 35)).map(|alarm| alarm.time),
+        Some(utc(5, 9, 40))
+    );
+    assert_eq!(events[0].next_alarm(utc(5, 9, 50)), None);
+}
+
+#[test]
+fn repetitions_past_the_range_of_dates_are_dropped() {
+    let events = parse(
+        "BEGIN:VEVENT\r\n\
+         DTSTART:20241205T100000Z\r\n\
+         BEGIN:VALARM\r\n\
+         TRIGGER:-PT30M\r\n\
+         REPEAT:100\r\n\
+         DURATION:P3000000D\r\n\
+         END:VALARM\r\n\
+         END:VEVENT\r\n",
+    );
+    // About 8200 years apart, the dates end after 31 repetitions
+    assert_eq!(events[0].alarms.len(), 32);
+    assert_eq!(events[0].alarms[0].time, utc(5, 9, 30));
+}
+
+#[test]
+fn all_day_events_use_the_default_timezone() {
+    let events = parse(
+        "BEGIN:VCALENDAR\r\n\
+         X-WR-TIMEZONE:Asia/Taipei\r\n\
+         BEGIN:VEVENT\r\n\
+         DTSTART;VALUE=DATE:20241205\r\n\
+         BEGIN:VALARM\r\n\
+         TRIGGER:-PT15H\r\n\
+         END:VALARM\r\n\
+         END:VEVENT\r\n\
+         END:VCALENDAR\r\n",
+    );
+    // 09:00 the day before in Taipei
+    assert_eq!(times(&events[0].alarms), vec![utc(4, 1, 0)]);
+}
+
+#[test]
+fn every_instance_has_its_own_alarms() {
+    let events = parse(
+        "BEGIN:VEVENT\r\n\
+         DTSTART:20241202T100000Z\r\n\
+         RRULE:FREQ=DAILY;COUNT=3\r\n\
+         BEGIN:VALARM\r\n\
+         TRIGGER:-PT10M\r\n\
+         END:VALARM\r\n\
+         END:VEVENT\r\n",
+    );
+    let alarms = events
+        .iter()
+        .flat_map(|event| times(&event.alarms))
+        .collect::<Vec<_>>();
+    assert_eq!(alarms, vec![utc(2, 9, 50), utc(3, 9, 50), utc(4, 9, 50)]);
+}
+
+#[test]
+fn broken_alarms_are_dropped() {
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_ics_chunk(
+            "BEGIN:VEVENT\r\n\
+             SUMMARY:Kept\r\n\
+             DTSTART:20241205T100000Z\r\n\
+             BEGIN:VALARM\r\n\
+             ACTION:DISPLAY\r\n\
+             END:VALARM\r\n\
+             BEGIN:VALARM\r\n\
+             TRIGGER:soon\r\n\
+             END:VALARM\r\n\
+             END:VEVENT\r\n",
+        )
+        .unwrap();
+    let output = parser.finish().unwrap();
+    assert_eq!(output.events.len(), 1);
+    assert!(output.events[0].alarms.is_empty());
+    assert_eq!(output.diagnostics.len(), 1);
+    assert_eq!(output.diagnostics[0].property, "TRIGGER");
+    assert_eq!(output.skipped_events, 0);
+}
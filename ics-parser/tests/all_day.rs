@@ -0,0 +1,105 @@
+mod common;
+
+use chrono::{TimeZone, Utc};
+use chrono_tz::{America, Asia, Pacific, Tz};
+use common::{date, parse};
+use ics_parser::{Event, EventTime, IcsParser};
+
+// The first event of the fixture, a one day holiday
+fn first_ntust_event(events: &[Event]) -> &Event {
+    let start = EventTime::Date(date(2024, 8, 21));
+    events.iter().find(|event| event.start == start).unwrap()
+}
+
+fn event(start: EventTime, end: EventTime) -> Event {
+    Event::new("", start, end)
+}
+
+#[test]
+fn date_values_stay_dates() {
+    let events = parse(include_str!("data/ntust.ics"));
+    let first = first_ntust_event(&events);
+    assert!(first.is_all_day());
+    assert_eq!(first.end, EventTime::Date(date(2024, 8, 22)));
+}
+
+#[test]
+fn all_day_events_keep_their_day_in_every_timezone() {
+    let events = parse(include_str!("data/ntust.ics"));
+    let first = first_ntust_event(&events);
+    let zones: [Tz; 4] = [
+        Asia::Taipei,
+        Pacific::Kiritimati,
+        America::Los_Angeles,
+        Pacific::Pago_Pago,
+    ];
+    for tz in zones {
+        assert_eq!(first.days(&tz), vec![date(2024, 8, 21)], "{}", tz);
+    }
+}
+
+#[test]
+fn multi_day_all_day_event() {
+    let holiday = event(date(2024, 10, 10).into(), date(2024, 10, 13).into());
+    assert_eq!(
+        holiday.days(&Asia::Taipei),
+        vec![date(2024, 10, 10), date(2024, 10, 11), date(2024, 10, 12)]
+    );
+    assert_eq!(holiday.last_day(&America::Los_Angeles), date(2024, 10, 12));
+}
+
+#[test]
+fn midnight_meeting_is_not_all_day() {
+    let events = parse(
+        "BEGIN:VEVENT\r\n\
+         DTSTART:20241205T000000Z\r\n\
+         DTEND:20241205T010000Z\r\n\
+         END:VEVENT\r\n",
+    );
+    assert!(!events[0].is_all_day());
+    assert_eq!(
+        events[0].start,
+        EventTime::DateTime(Utc.with_ymd_and_hms(2024, 12, 5, 0, 0, 0).unwrap())
+    );
+    // Still the evening before on the west coast
+    assert_eq!(
+        events[0].days(&America::Los_Angeles),
+        vec![date(2024, 12, 4)]
+    );
+}
+
+#[test]
+fn timed_event_ending_at_local_midnight() {
+    // 22:00 to 00:00 in Taipei
+    let late = event(
+        Utc.with_ymd_and_hms(2024, 12, 5, 14, 0, 0).unwrap().into(),
+        Utc.with_ymd_and_hms(2024, 12, 5, 16, 0, 0).unwrap().into(),
+    );
+    assert_eq!(late.days(&Asia::Taipei), vec![date(2024, 12, 5)]);
+
+    // 22:00 to 01:00 in Taipei
+    let later = event(
+        Utc.with_ymd_and_hms(2024, 12, 5, 14, 0, 0).unwrap().into(),
+        Utc.with_ymd_and_hms(2024, 12, 5, 17, 0, 0).unwrap().into(),
+    );
+    assert_eq!(
+        later.days(&Asia::Taipei),
+        vec![date(2024, 12, 5), date(2024, 12, 6)]
+    );
+}
+
+#[test]
+fn all_day_events_use_local_midnight_for_the_window() {
+    // The window starts at midnight in Taipei, which is still the previous day in UTC
+    let mut parser = IcsParser::new(
+        Some(Utc.with_ymd_and_hms(2024, 8, 20, 16, 0, 0).unwrap()),
+        Some(Utc.with_ymd_and_hms(2024, 8, 21, 16, 0, 0).unwrap()),
+    )
+    .default_timezone(Asia::Taipei);
+    parser
+        .parse_ics_chunk(include_str!("data/ntust.ics"))
+        .unwrap();
+    let events = parser.get_events();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].first_day(&Asia::Taipei), date(2024, 8, 21));
+}
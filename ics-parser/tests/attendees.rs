@@ -0,0 +1,74 @@
+use ics_parser::{Event, IcsParser, ParticipationStatus};
+
+const INVITATIONS: &str = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+UID:planning@example.com\r\n\
+SUMMARY:Planning\r\n\
+DTSTART:20241205T060000Z\r\n\
+DTEND:20241205T070000Z\r\n\
+ORGANIZER;CN=Alice:mailto:alice@example.com\r\n\
+ATTENDEE;CUTYPE=INDIVIDUAL;ROLE=REQ-PARTICIPANT;PARTSTAT=ACCEPTED;CN=Alice:mailto:alice@example.com\r\n\
+ATTENDEE;CN=\"Chen, Bob\";PARTSTAT=DECLINED;X-NUM-GUESTS=0:MAILTO:Bob@Example.com\r\n\
+ATTENDEE;RSVP=TRUE:mailto:carol@example.com\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:review@example.com\r\n\
+SUMMARY:Review\r\n\
+STATUS:CANCELLED\r\n\
+DTSTART:20241206T060000Z\r\n\
+DTEND:20241206T070000Z\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:lunch@example.com\r\n\
+SUMMARY:Lunch\r\n\
+TRANSP:TRANSPARENT\r\n\
+DTSTART:20241207T040000Z\r\n\
+DTEND:20241207T050000Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+fn parse() -> Vec<Event> {
+    let mut parser = IcsParser::new(None, None);
+    parser.parse_ics_chunk(INVITATIONS).unwrap();
+    let mut events = parser.get_events();
+    events.sort_by_key(|event| event.start);
+    events
+}
+
+#[test]
+fn parses_attendees() {
+    let events = parse();
+    let attendees = &events[0].attendees;
+    assert_eq!(attendees.len(), 3);
+    assert_eq!(attendees[0].email(), "alice@example.com");
+    assert_eq!(
+        attendees[0].participation_status,
+        ParticipationStatus::Accepted
+    );
+    assert_eq!(attendees[1].common_name.as_deref(), Some("Chen, Bob"));
+    assert_eq!(attendees[1].address, "MAILTO:Bob@Example.com");
+    assert_eq!(attendees[1].email(), "Bob@Example.com");
+    // RFC 5545 default
+    assert_eq!(
+        attendees[2].participation_status,
+        ParticipationStatus::NeedsAction
+    );
+}
+
+#[test]
+fn declined_by_attendee() {
+    let events = parse();
+    assert!(events[0].is_declined_by("bob@example.com"));
+    assert!(!events[0].is_declined_by("alice@example.com"));
+    assert!(!events[0].is_declined_by("dave@example.com"));
+}
+
+#[test]
+fn cancelled_and_transparent_events() {
+    let events = parse();
+    let flags = events
+        .iter()
+        .map(|event| (event.is_cancelled(), event.is_transparent()))
+        .collect::<Vec<_>>();
+    assert_eq!(flags, vec![(false, false), (true, false), (false, true)]);
+}
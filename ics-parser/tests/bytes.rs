@@ -0,0 +1,80 @@
+use ics_parser::{Event, IcsParser};
+
+const NTUST: &str = include_str!("data/ntust.ics");
+
+fn parse_bytes(chunks: &[&[u8]]) -> Vec<Event> {
+    let mut parser = IcsParser::new(None, None);
+    for chunk in chunks {
+        parser.parse_ics_bytes(chunk).unwrap();
+    }
+    parser.get_events()
+}
+
+fn summaries(events: &[Event]) -> Vec<&str> {
+    events.iter().map(|event| event.summary.as_str()).collect()
+}
+
+#[test]
+fn splits_cjk_text_inside_every_character() {
+    let expected = parse_bytes(&[NTUST.as_bytes()]);
+    assert!(expected.iter().any(|event| !event.summary.is_ascii()));
+
+    let bytes = NTUST.as_bytes();
+    // Splits between characters never reach the UTF-8 handling
+    for split in (1..bytes.len()).filter(|split| !NTUST.is_char_boundary(*split)) {
+        let events = parse_bytes(&[&bytes[..split], &bytes[split..]]);
+        assert_eq!(
+            summaries(&events),
+            summaries(&expected),
+            "split at byte {}",
+            split
+        );
+    }
+}
+
+#[test]
+fn splits_at_every_byte_offset() {
+    let bytes = NTUST.as_bytes();
+    let expected = parse_bytes(&[bytes]);
+    // One byte chunks put a boundary at every offset, longer chunks at every phase
+    // leave complete characters in between
+    for size in 1..=8 {
+        for phase in 0..size {
+            let (head, tail) = bytes.split_at(phase);
+            let mut chunks = vec![head];
+            chunks.extend(tail.chunks(size));
+            assert_eq!(
+                summaries(&parse_bytes(&chunks)),
+                summaries(&expected),
+                "chunks of {} bytes after {}",
+                size,
+                phase
+            );
+        }
+    }
+}
+
+#[test]
+fn invalid_bytes_are_replaced() {
+    let mut ics = b"BEGIN:VEVENT\r\nSUMMARY:Caf\xe9 \xe4\xbd".to_vec();
+    ics.extend_from_slice(b"\r\nDTSTART:20241205T100000Z\r\nEND:VEVENT\r\n");
+    let events = parse_bytes(&[&ics[..20], &ics[20..]]);
+    assert_eq!(events[0].summary, "Caf\u{fffd} \u{fffd}");
+}
+
+#[test]
+fn truncated_character_at_the_end() {
+    let events = parse_bytes(&[
+        b"BEGIN:VEVENT\r\nDTSTART:20241205T100000Z\r\nSUMMARY:\xe4\xbd\x9c",
+        b"\xe6\xa5",
+    ]);
+    assert!(events.is_empty());
+
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_ics_bytes(
+            b"BEGIN:VEVENT\r\nDTSTART:20241205T100000Z\r\nEND:VEVENT\r\nX-NOTE:\xe4\xbd",
+        )
+        .unwrap();
+    assert_eq!(parser.get_events().len(), 1);
+}
@@ -0,0 +1,50 @@
+use ics_parser::{CalendarInfo, IcsParser};
+
+fn calendar(ics: &str) -> CalendarInfo {
+    let mut parser = IcsParser::new(None, None);
+    parser.parse_ics_chunk(ics).unwrap();
+    parser.finish().unwrap().calendar
+}
+
+#[test]
+fn names_and_colors_of_the_fixtures() {
+    let ntust = calendar(include_str!("data/ntust.ics"));
+    assert_eq!(ntust.name.as_deref(), Some("NTUST 113"));
+    assert_eq!(ntust.color.as_deref(), Some("#1BADF8"));
+    assert_eq!(ntust.rgb(), Some((0x1b, 0xad, 0xf8)));
+    assert_eq!(
+        ntust.product_id.as_deref(),
+        Some("-//caldav.icloud.com//CALDAVJ 2428B596//EN")
+    );
+    assert_eq!(ntust.method, None);
+
+    let moodle = calendar(include_str!("data/moodle.ics"));
+    assert_eq!(moodle.name, None);
+    assert_eq!(moodle.method.as_deref(), Some("PUBLISH"));
+
+    let google = calendar(include_str!("data/google.ics"));
+    assert_eq!(google.name.as_deref(), Some("Team Schedule"));
+    assert_eq!(google.timezone.as_deref(), Some("Asia/Taipei"));
+    assert_eq!(google.rgb(), None);
+}
+
+#[test]
+fn calendar_properties_are_unescaped() {
+    let calendar = calendar(
+        "BEGIN:VCALENDAR\r\n\
+         X-WR-CALNAME:Lab\\, room 3\r\n\
+         X-WR-CALDESC:Shared\\nschedule\r\n\
+         X-APPLE-CALENDAR-COLOR:#FF2968FF\r\n\
+         X-WR-TIMEZONE:Nowhere\r\n\
+         BEGIN:VEVENT\r\n\
+         X-WR-CALNAME:Not the calendar\r\n\
+         DTSTART:20241205T100000Z\r\n\
+         END:VEVENT\r\n\
+         END:VCALENDAR\r\n",
+    );
+    assert_eq!(calendar.name.as_deref(), Some("Lab, room 3"));
+    assert_eq!(calendar.description.as_deref(), Some("Shared\nschedule"));
+    assert_eq!(calendar.rgb(), Some((0xff, 0x29, 0x68)));
+    // Kept even though the timezone is unknown
+    assert_eq!(calendar.timezone.as_deref(), Some("Nowhere"));
+}
@@ -0,0 +1,38 @@
+// Helpers shared by the integration tests, each test file uses only some of them
+#![allow(dead_code)]
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use ics_parser::{Diagnostic, DiagnosticKind, Event, EventTime, IcsParser};
+
+pub fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> EventTime {
+    Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap().into()
+}
+
+pub fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+// The events of a feed, from the earliest
+pub fn parse(ics: &str) -> Vec<Event> {
+    let mut parser = IcsParser::new(None, None);
+    parser.parse_ics_chunk(ics).unwrap();
+    let mut events = parser.get_events();
+    events.sort_by_key(|event| event.start);
+    events
+}
+
+// A calendar with one event made of `properties`, one per line
+pub fn event_ics(properties: &str) -> String {
+    format!(
+        "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Event\r\n{}\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        properties.trim().replace('\n', "\r\n")
+    )
+}
+
+pub fn diagnostic(line: usize, property: &str, kind: DiagnosticKind) -> Diagnostic {
+    Diagnostic {
+        line,
+        property: property.to_string(),
+        kind,
+    }
+}
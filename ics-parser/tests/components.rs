@@ -0,0 +1,146 @@
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::diagnostic;
+use ics_parser::{DiagnosticKind, EventTime, IcsParser};
+
+#[test]
+fn alarm_properties_stay_in_the_alarm() {
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_ics_chunk(
+            "BEGIN:VCALENDAR\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:Meeting\r\n\
+             DESCRIPTION:Agenda\r\n\
+             DTSTART:20241205T100000Z\r\n\
+             BEGIN:VALARM\r\n\
+             ACTION:DISPLAY\r\n\
+             DESCRIPTION:Reminder\r\n\
+             TRIGGER:-PT15M\r\n\
+             END:VALARM\r\n\
+             DTEND:20241205T110000Z\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n",
+        )
+        .unwrap();
+    let output = parser.finish().unwrap();
+    assert_eq!(output.events.len(), 1);
+    assert_eq!(output.events[0].description.as_deref(), Some("Agenda"));
+    assert!(output.diagnostics.is_empty());
+}
+
+#[test]
+fn properties_outside_events_are_ignored() {
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_ics_chunk(
+            "BEGIN:VCALENDAR\r\n\
+             SUMMARY:Calendar\r\n\
+             BEGIN:VTODO\r\n\
+             SUMMARY:Task\r\n\
+             DTSTART:20241201T100000Z\r\n\
+             END:VTODO\r\n\
+             BEGIN:VEVENT\r\n\
+             DTSTART:20241205T100000Z\r\n\
+             END:VEVENT\r\n\
+             DTSTART:20241210T100000Z\r\n\
+             END:VCALENDAR\r\n",
+        )
+        .unwrap();
+    let events = parser.get_events();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].summary, "");
+    assert_eq!(
+        events[0].start,
+        EventTime::from(Utc.with_ymd_and_hms(2024, 12, 5, 10, 0, 0).unwrap())
+    );
+}
+
+#[test]
+fn truncated_feed_is_reported() {
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_ics_chunk(
+            "BEGIN:VCALENDAR\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:Complete\r\n\
+             DTSTART:20241205T100000Z\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:Cut off\r\n\
+             DTSTART:20241206T100000Z\r\n",
+        )
+        .unwrap();
+    let output = parser.finish().unwrap();
+    assert_eq!(output.events.len(), 1);
+    assert_eq!(output.skipped_events, 1);
+    assert_eq!(
+        output.diagnostics,
+        vec![
+            diagnostic(8, "VEVENT", DiagnosticKind::MissingEnd),
+            diagnostic(8, "VCALENDAR", DiagnosticKind::MissingEnd),
+        ]
+    );
+}
+
+#[test]
+fn unbalanced_components_are_reported() {
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_ics_chunk(
+            "BEGIN:VCALENDAR\r\n\
+             BEGIN:VEVENT\r\n\
+             DTSTART:20241205T100000Z\r\n\
+             BEGIN:VALARM\r\n\
+             TRIGGER:-PT15M\r\n\
+             END:VEVENT\r\n\
+             END:VALARM\r\n\
+             END:VCALENDAR\r\n",
+        )
+        .unwrap();
+    let output = parser.finish().unwrap();
+    assert_eq!(output.events.len(), 1);
+    assert_eq!(
+        output.diagnostics,
+        vec![
+            diagnostic(6, "VALARM", DiagnosticKind::MissingEnd),
+            diagnostic(7, "VALARM", DiagnosticKind::UnexpectedEnd),
+        ]
+    );
+
+    let mut parser = IcsParser::new(None, None).strict(true);
+    let error = parser.parse_ics_chunk("END:VEVENT\r\nBEGIN:VCALENDAR\r\n");
+    assert_eq!(
+        error.unwrap_err().to_string(),
+        "Failed to parse calendar, line 1: VEVENT: END without BEGIN"
+    );
+}
+
+#[test]
+fn timezone_start_does_not_leak_into_events() {
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_ics_chunk(
+            "BEGIN:VCALENDAR\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:No start\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VTIMEZONE\r\n\
+             TZID:Office\r\n\
+             BEGIN:STANDARD\r\n\
+             DTSTART:19700101T000000\r\n\
+             TZOFFSETFROM:+0800\r\n\
+             TZOFFSETTO:+0800\r\n\
+             END:STANDARD\r\n\
+             END:VTIMEZONE\r\n\
+             END:VCALENDAR\r\n",
+        )
+        .unwrap();
+    let output = parser.finish().unwrap();
+    assert!(output.events.is_empty());
+    assert_eq!(
+        output.diagnostics,
+        vec![diagnostic(4, "VEVENT", DiagnosticKind::MissingStart)]
+    );
+}
@@ -0,0 +1,113 @@
+use chrono::{TimeZone, Utc};
+use chrono_tz::America::New_York;
+use ics_parser::{ContentLine, DiagnosticKind, EventTime, IcsParser, Parameter};
+
+fn parameter(name: &str, values: &[&str]) -> Parameter {
+    Parameter {
+        name: name.to_string(),
+        values: values.iter().map(|value| value.to_string()).collect(),
+    }
+}
+
+#[test]
+fn splits_name_parameters_and_value() {
+    let line = ContentLine::parse(
+        "ATTENDEE;CN=\"Doe; John: PhD\";MEMBER=\"mailto:a@example.com\",\"mailto:b@example.com\"\
+         ;role=REQ-PARTICIPANT:mailto:john@example.com",
+    )
+    .unwrap();
+    assert_eq!(line.name, "ATTENDEE");
+    assert_eq!(
+        line.parameters,
+        vec![
+            parameter("CN", &["Doe; John: PhD"]),
+            parameter("MEMBER", &["mailto:a@example.com", "mailto:b@example.com"]),
+            parameter("ROLE", &["REQ-PARTICIPANT"]),
+        ]
+    );
+    assert_eq!(line.value, "mailto:john@example.com");
+    assert_eq!(line.parameter("Role"), Some("REQ-PARTICIPANT"));
+    assert_eq!(line.parameter_values("MEMBER").len(), 2);
+    assert_eq!(line.parameter("LANGUAGE"), None);
+}
+
+#[test]
+fn value_is_kept_as_written() {
+    let line = ContentLine::parse("description:Room 1\\; floor 2: east, wing").unwrap();
+    assert_eq!(line.name, "DESCRIPTION");
+    assert!(line.parameters.is_empty());
+    assert_eq!(line.value, "Room 1\\; floor 2: east, wing");
+
+    assert_eq!(ContentLine::parse("X-EMPTY:").unwrap().value, "");
+}
+
+#[test]
+fn rejects_malformed_lines() {
+    for line in [
+        "no separator",
+        ":value",
+        "DTSTART;TZID:20241205T100000",
+        "DTSTART;TZID=\"Asia/Taipei:20241205T100000",
+        "DTSTART;TZID=Asia/Taipei",
+        "SUMMARY WITH SPACES:value",
+    ] {
+        assert_eq!(ContentLine::parse(line), None, "{}", line);
+    }
+}
+
+#[test]
+fn parameters_in_any_order_and_case() {
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_ics_chunk(
+            "begin:vcalendar\r\n\
+             begin:vevent\r\n\
+             summary;language=en:Lowercase\r\n\
+             dtstart;value=date-time;tzid=\"America/New_York\":20241205T100000\r\n\
+             DTEND;TZID=America/New_York;VALUE=DATE-TIME:20241205T110000\r\n\
+             end:vevent\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:Dates\r\n\
+             DTSTART;X-FOO=\"a:b\";VALUE=DATE:20241206\r\n\
+             END:VEVENT\r\n\
+             end:vcalendar\r\n",
+        )
+        .unwrap();
+    let output = parser.finish().unwrap();
+    assert!(output.diagnostics.is_empty());
+
+    let events = output.events;
+    assert_eq!(events[0].summary, "Lowercase");
+    assert_eq!(
+        events[0].start,
+        EventTime::from(
+            New_York
+                .with_ymd_and_hms(2024, 12, 5, 10, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc)
+        )
+    );
+    assert_eq!(
+        events[0].end,
+        EventTime::from(Utc.with_ymd_and_hms(2024, 12, 5, 16, 0, 0).unwrap())
+    );
+    assert!(events[1].is_all_day());
+}
+
+#[test]
+fn malformed_lines_are_reported() {
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_ics_chunk(
+            "BEGIN:VEVENT\r\n\
+             DTSTART:20241205T100000Z\r\n\
+             Just some text\r\n\
+             END:VEVENT\r\n",
+        )
+        .unwrap();
+    let output = parser.finish().unwrap();
+    assert_eq!(output.events.len(), 1);
+    assert_eq!(output.diagnostics.len(), 1);
+    assert_eq!(output.diagnostics[0].line, 3);
+    assert_eq!(output.diagnostics[0].kind, DiagnosticKind::MalformedLine);
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use common::diagnostic;
+use ics_parser::{DiagnosticKind, IcsParser, MAX_DIAGNOSTICS};
+
+const BROKEN: &str = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Unknown timezone\r\n\
+DTSTART;TZID=Mars/Olympus_Mons:20241205T100000\r\n\
+DTEND;TZID=Mars/Olympus_Mons:20241205T110000\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Fine\r\n\
+DTSTART:20241205T100000Z\r\n\
+DTEND:20241205T110000Z\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Odd format\r\n\
+DTSTART:2024-12-05 10\r\n\
+\x20:00\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:No start\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+#[test]
+fn lenient_mode_skips_and_counts_bad_events() {
+    let mut parser = IcsParser::new(None, None);
+    parser.parse_ics_chunk(BROKEN).unwrap();
+    let output = parser.finish().unwrap();
+
+    let summaries = output
+        .events
+        .iter()
+        .map(|event| event.summary.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(summaries, vec!["Fine"]);
+    assert_eq!(output.skipped_events, 3);
+    assert_eq!(
+        output.diagnostics,
+        vec![
+            diagnostic(
+                4,
+                "DTSTART",
+                DiagnosticKind::UnknownTimezone("Mars/Olympus_Mons".to_string())
+            ),
+            diagnostic(
+                5,
+                "DTEND",
+                DiagnosticKind::UnknownTimezone("Mars/Olympus_Mons".to_string())
+            ),
+            diagnostic(
+                14,
+                "DTSTART",
+                DiagnosticKind::InvalidValue("2024-12-05 10:00".to_string())
+            ),
+            diagnostic(19, "VEVENT", DiagnosticKind::MissingStart),
+        ]
+    );
+}
+
+#[test]
+fn strict_mode_stops_at_the_first_problem() {
+    let mut parser = IcsParser::new(None, None).strict(true);
+    let error = parser.parse_ics_chunk(BROKEN).unwrap_err();
+    assert_eq!(
+        error.diagnostic,
+        diagnostic(
+            4,
+            "DTSTART",
+            DiagnosticKind::UnknownTimezone("Mars/Olympus_Mons".to_string())
+        )
+    );
+    assert_eq!(
+        error.to_string(),
+        "Failed to parse calendar, line 4: DTSTART: unknown timezone `Mars/Olympus_Mons`"
+    );
+}
+
+#[test]
+fn strict_mode_reports_the_last_line() {
+    let mut parser = IcsParser::new(None, None).strict(true);
+    parser
+        .parse_ics_chunk("BEGIN:VEVENT\r\nDTSTART:20241205T100000Z\r\nRRULE:FREQ=SOMETIMES")
+        .unwrap();
+    let error = parser.finish().unwrap_err();
+    assert_eq!(error.diagnostic.line, 3);
+    assert_eq!(error.diagnostic.property, "RRULE");
+}
+
+#[test]
+fn fixtures_parse_without_problems() {
+    for ics in [
+        include_str!("data/ntust.ics"),
+        include_str!("data/moodle.ics"),
+        include_str!("data/google.ics"),
+        include_str!("data/icloud.ics"),
+        include_str!("data/outlook.ics"),
+    ] {
+        let mut parser = IcsParser::new(None, None).strict(true);
+        parser.parse_ics_chunk(ics).unwrap();
+        let output = parser.finish().unwrap();
+        assert_eq!(output.skipped_events, 0);
+    }
+}
+
+#[test]
+fn problems_in_timezone_definitions() {
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_ics_chunk(
+            "BEGIN:VCALENDAR\r\n\
+             X-WR-TIMEZONE:Nowhere\r\n\
+             BEGIN:VTIMEZONE\r\n\
+             TZID:Office\r\n\
+             BEGIN:STANDARD\r\n\
+             DTSTART:19700101T000000\r\n\
+             TZOFFSETFROM:+8\r\n\
+             TZOFFSETTO:+0800\r\n\
+             END:STANDARD\r\n\
+             END:VTIMEZONE\r\n\
+             END:VCALENDAR\r\n",
+        )
+        .unwrap();
+    let output = parser.finish().unwrap();
+    assert_eq!(
+        output.diagnostics,
+        vec![
+            diagnostic(
+                2,
+                "X-WR-TIMEZONE",
+                DiagnosticKind::UnknownTimezone("Nowhere".to_string())
+            ),
+            diagnostic(
+                7,
+                "TZOFFSETFROM",
+                DiagnosticKind::InvalidValue("+8".to_string())
+            ),
+        ]
+    );
+    assert_eq!(output.skipped_events, 0);
+}
+
+#[test]
+fn diagnostics_past_the_limit_are_counted() {
+    let mut ics = String::from("BEGIN:VCALENDAR\r\n");
+    for _ in 0..MAX_DIAGNOSTICS + 50 {
+        ics.push_str(
+            "BEGIN:VEVENT\r\n\
+             DTSTART;TZID=Mars/Olympus_Mons:20241205T100000\r\n\
+             END:VEVENT\r\n",
+        );
+    }
+    ics.push_str("END:VCALENDAR\r\n");
+
+    let mut parser = IcsParser::new(None, None);
+    parser.parse_ics_chunk(&ics).unwrap();
+    let output = parser.finish().unwrap();
+    assert_eq!(output.diagnostics.len(), MAX_DIAGNOSTICS);
+    assert_eq!(output.diagnostics[0].line, 3);
+    assert_eq!(output.omitted_diagnostics, 50);
+    assert_eq!(output.skipped_events, MAX_DIAGNOSTICS + 50);
+}
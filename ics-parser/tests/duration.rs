@@ -0,0 +1,134 @@
+mod common;
+
+use common::{date, diagnostic, event_ics, utc};
+use ics_parser::{DiagnosticKind, EventTime, IcsParser};
+
+fn parse(properties: &str) -> Vec<(EventTime, EventTime)> {
+    common::parse(&event_ics(properties))
+        .into_iter()
+        .map(|event| (event.start, event.end))
+        .collect()
+}
+
+#[test]
+fn duration_instead_of_dtend() {
+    let events = parse(
+        "DTSTART:20241205T100000Z
+DURATION:PT1H30M",
+    );
+    assert_eq!(
+        events,
+        vec![(utc(2024, 12, 5, 10, 0), utc(2024, 12, 5, 11, 30))]
+    );
+}
+
+#[test]
+fn duration_in_days_and_weeks() {
+    let events = parse(
+        "DTSTART;VALUE=DATE:20241205
+DURATION:P2D",
+    );
+    assert_eq!(
+        events,
+        vec![(
+            EventTime::Date(date(2024, 12, 5)),
+            EventTime::Date(date(2024, 12, 7))
+        )]
+    );
+
+    let events = parse(
+        "DTSTART:20241205T100000Z
+DURATION:P1W",
+    );
+    assert_eq!(
+        events,
+        vec![(utc(2024, 12, 5, 10, 0), utc(2024, 12, 12, 10, 0))]
+    );
+}
+
+#[test]
+fn nominal_days_keep_the_wall_clock_time_across_dst() {
+    // New York falls back on 2024-11-03
+    let events = parse(
+        "DTSTART;TZID=America/New_York:20241102T090000
+DURATION:P1DT1H",
+    );
+    assert_eq!(
+        events,
+        vec![(utc(2024, 11, 2, 13, 0), utc(2024, 11, 3, 15, 0))]
+    );
+
+    let events = parse(
+        "DTSTART;TZID=America/New_York:20241103T003000
+DURATION:PT2H",
+    );
+    assert_eq!(
+        events,
+        vec![(utc(2024, 11, 3, 4, 30), utc(2024, 11, 3, 6, 30))]
+    );
+}
+
+#[test]
+fn missing_end_defaults() {
+    let events = parse("DTSTART;VALUE=DATE:20241205");
+    assert_eq!(
+        events,
+        vec![(
+            EventTime::Date(date(2024, 12, 5)),
+            EventTime::Date(date(2024, 12, 6))
+        )]
+    );
+
+    let events = parse("DTSTART:20241205T100000Z");
+    assert_eq!(
+        events,
+        vec![(utc(2024, 12, 5, 10, 0), utc(2024, 12, 5, 10, 0))]
+    );
+}
+
+#[test]
+fn recurring_event_with_duration() {
+    let events = parse(
+        "DTSTART;TZID=Asia/Taipei:20241202T090000
+DURATION:PT45M
+RRULE:FREQ=DAILY;COUNT=3",
+    );
+    assert_eq!(
+        events,
+        vec![
+            (utc(2024, 12, 2, 1, 0), utc(2024, 12, 2, 1, 45)),
+            (utc(2024, 12, 3, 1, 0), utc(2024, 12, 3, 1, 45)),
+            (utc(2024, 12, 4, 1, 0), utc(2024, 12, 4, 1, 45)),
+        ]
+    );
+}
+
+#[test]
+fn invalid_duration_skips_the_event() {
+    let events = parse(
+        "DTSTART;VALUE=DATE:20241205
+DURATION:P1M",
+    );
+    assert_eq!(events, vec![]);
+}
+
+#[test]
+fn durations_past_the_range_of_dates_are_invalid() {
+    for duration in ["P4000000000D", "PT4000000000H", "-P20000000W"] {
+        let ics = event_ics(&format!("DTSTART:20241205T100000Z\nDURATION:{}", duration));
+        let mut parser = IcsParser::new(None, None);
+        parser.parse_ics_chunk(&ics).unwrap();
+        let output = parser.finish().unwrap();
+
+        assert!(output.events.is_empty());
+        assert_eq!(output.skipped_events, 1);
+        assert_eq!(
+            output.diagnostics,
+            vec![diagnostic(
+                5,
+                "DURATION",
+                DiagnosticKind::InvalidValue(duration.to_string())
+            )]
+        );
+    }
+}
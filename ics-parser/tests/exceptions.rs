@@ -0,0 +1,107 @@
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::{date, utc};
+use ics_parser::{EventTime, IcsParser};
+
+fn parse(ics: &str) -> Vec<(String, EventTime, EventTime)> {
+    let mut parser = IcsParser::new(
+        Some(Utc.with_ymd_and_hms(2024, 12, 1, 0, 0, 0).unwrap()),
+        Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()),
+    );
+    // Small chunks, so overrides and masters are split across calls
+    for chunk in ics.as_bytes().chunks(64) {
+        parser
+            .parse_ics_chunk(std::str::from_utf8(chunk).unwrap())
+            .unwrap();
+    }
+    let mut events = parser.get_events();
+    events.sort_by_key(|event| event.start);
+    events
+        .into_iter()
+        .map(|event| (event.summary, event.start, event.end))
+        .collect()
+}
+
+fn event(
+    summary: &str,
+    start: impl Into<EventTime>,
+    end: impl Into<EventTime>,
+) -> (String, EventTime, EventTime) {
+    (summary.to_string(), start.into(), end.into())
+}
+
+#[test]
+fn google_exceptions_and_overrides() {
+    let events = parse(include_str!("data/google.ics"));
+    assert_eq!(
+        events,
+        vec![
+            event("Stand-up", utc(2024, 12, 2, 1, 30), utc(2024, 12, 2, 1, 45)),
+            event(
+                "Software Engineering",
+                utc(2024, 12, 2, 2, 0),
+                utc(2024, 12, 2, 4, 0)
+            ),
+            event("Stand-up", utc(2024, 12, 3, 1, 30), utc(2024, 12, 3, 1, 45)),
+            event(
+                "Stand-up + sprint review",
+                utc(2024, 12, 4, 1, 30),
+                utc(2024, 12, 4, 2, 0)
+            ),
+            event("Stand-up", utc(2024, 12, 5, 1, 30), utc(2024, 12, 5, 1, 45)),
+            event("Stand-up", utc(2024, 12, 6, 1, 30), utc(2024, 12, 6, 1, 45)),
+            event(
+                "Software Engineering (Makeup class)",
+                utc(2024, 12, 11, 6, 0),
+                utc(2024, 12, 11, 8, 0)
+            ),
+            event(
+                "Software Engineering",
+                utc(2024, 12, 23, 2, 0),
+                utc(2024, 12, 23, 4, 0)
+            ),
+            event("Christmas party", date(2024, 12, 25), date(2024, 12, 26)),
+            event(
+                "Software Engineering",
+                utc(2024, 12, 30, 2, 0),
+                utc(2024, 12, 30, 4, 0)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn icloud_rdates_and_override_before_master() {
+    let events = parse(include_str!("data/icloud.ics"));
+    assert_eq!(
+        events,
+        vec![
+            event(
+                "Badminton",
+                utc(2024, 12, 3, 11, 30),
+                utc(2024, 12, 3, 13, 0)
+            ),
+            event("Farmers market", date(2024, 12, 7), date(2024, 12, 8)),
+            event(
+                "Badminton (moved to Thursday)",
+                utc(2024, 12, 12, 11, 30),
+                utc(2024, 12, 12, 13, 0)
+            ),
+            event("Farmers market", date(2024, 12, 21), date(2024, 12, 22)),
+            event(
+                "Badminton",
+                utc(2024, 12, 21, 2, 0),
+                utc(2024, 12, 21, 3, 30)
+            ),
+            event(
+                "Badminton",
+                utc(2024, 12, 24, 11, 30),
+                utc(2024, 12, 24, 13, 0)
+            ),
+            event("Farmers market", date(2024, 12, 25), date(2024, 12, 26)),
+            event("Farmers market", date(2024, 12, 26), date(2024, 12, 27)),
+            event("Farmers market", date(2024, 12, 28), date(2024, 12, 29)),
+        ]
+    );
+}
@@ -0,0 +1,263 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use ics_parser::{DiagnosticKind, Event, EventTime, IcsParser, ParseOutput, ParticipationStatus};
+
+const ICS: &str = "BEGIN:VCALENDAR\r\n\
+    VERSION:2.0\r\n\
+    PRODID:-//Nextcloud calendar//EN\r\n\
+    X-WR-CALNAME:Team\\, shared\r\n\
+    BEGIN:VTIMEZONE\r\n\
+    TZID:Custom/Taipei\r\n\
+    BEGIN:STANDARD\r\n\
+    DTSTART:19700101T000000\r\n\
+    TZOFFSETFROM:+0800\r\n\
+    TZOFFSETTO:+0800\r\n\
+    END:STANDARD\r\n\
+    END:VTIMEZONE\r\n\
+    BEGIN:VEVENT\r\n\
+    UID:standup@example.com\r\n\
+    DTSTART;TZID=Custom/Taipei:20241202T093000\r\n\
+    DTEND;TZID=Custom/Taipei:20241202T100000\r\n\
+    RRULE:FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20241212T000000Z\r\n\
+    EXDATE;TZID=Custom/Taipei:20241204T093000\r\n\
+    SUMMARY:Standup\\; daily\\, short\r\n\
+    DESCRIPTION:Line one\\nLine two\r\n\
+    CATEGORIES:Work,Team\\, all\r\n\
+    SEQUENCE:2\r\n\
+    X-CUSTOM:kept\r\n\
+    ATTENDEE;CN=\"Doe, John\";PARTSTAT=DECLINED:mailto:doe@example.com\r\n\
+    BEGIN:VALARM\r\n\
+    ACTION:DISPLAY\r\n\
+    TRIGGER:-PT15M\r\n\
+    END:VALARM\r\n\
+    BEGIN:VALARM\r\n\
+    ACTION:AUDIO\r\n\
+    TRIGGER;VALUE=DATE-TIME:20241201T120000Z\r\n\
+    END:VALARM\r\n\
+    END:VEVENT\r\n\
+    BEGIN:VEVENT\r\n\
+    UID:holiday@example.com\r\n\
+    DTSTART;VALUE=DATE:20241225\r\n\
+    SUMMARY:Holiday\r\n\
+    TRANSP:TRANSPARENT\r\n\
+    END:VEVENT\r\n\
+    BEGIN:VTODO\r\n\
+    UID:report@example.com\r\n\
+    SUMMARY:Report\r\n\
+    DUE;VALUE=DATE:20241220\r\n\
+    END:VTODO\r\n\
+    END:VCALENDAR\r\n";
+
+// The same calendar as jCal
+const JCAL: &str = r#"["vcalendar",
+  [
+    ["version", {}, "text", "2.0"],
+    ["prodid", {}, "text", "-//Nextcloud calendar//EN"],
+    ["x-wr-calname", {}, "unknown", "Team\\, shared"]
+  ],
+  [
+    ["vtimezone",
+      [["tzid", {}, "text", "Custom/Taipei"]],
+      [
+        ["standard",
+          [
+            ["dtstart", {}, "date-time", "1970-01-01T00:00:00"],
+            ["tzoffsetfrom", {}, "utc-offset", "+08:00"],
+            ["tzoffsetto", {}, "utc-offset", "+08:00"]
+          ],
+          []
+        ]
+      ]
+    ],
+    ["vevent",
+      [
+        ["uid", {}, "text", "standup@example.com"],
+        ["dtstart", {"tzid": "Custom/Taipei"}, "date-time", "2024-12-02T09:30:00"],
+        ["dtend", {"tzid": "Custom/Taipei"}, "date-time", "2024-12-02T10:00:00"],
+        ["rrule", {}, "recur", {"byday": ["MO", "WE"], "freq": "WEEKLY", "until": "2024-12-12T00:00:00Z"}],
+        ["exdate", {"tzid": "Custom/Taipei"}, "date-time", "2024-12-04T09:30:00"],
+        ["summary", {}, "text", "Standup; daily, short"],
+        ["description", {}, "text", "Line one\nLine two"],
+        ["categories", {}, "text", "Work", "Team, all"],
+        ["sequence", {}, "integer", 2],
+        ["x-custom", {}, "unknown", "kept"],
+        ["attendee", {"cn": "Doe, John", "partstat": "DECLINED"}, "cal-address", "mailto:doe@example.com"]
+      ],
+      [
+        ["valarm",
+          [["action", {}, "text", "DISPLAY"], ["trigger", {}, "duration", "-PT15M"]],
+          []
+        ],
+        ["valarm",
+          [["action", {}, "text", "AUDIO"], ["trigger", {}, "date-time", "2024-12-01T12:00:00Z"]],
+          []
+        ]
+      ]
+    ],
+    ["vevent",
+      [
+        ["uid", {}, "text", "holiday@example.com"],
+        ["dtstart", {}, "date", "2024-12-25"],
+        ["summary", {}, "text", "Holiday"],
+        ["transp", {}, "text", "TRANSPARENT"]
+      ],
+      []
+    ],
+    ["vtodo",
+      [
+        ["uid", {}, "text", "report@example.com"],
+        ["summary", {}, "text", "Report"],
+        ["due", {}, "date", "2024-12-20"]
+      ],
+      []
+    ]
+  ]
+]"#;
+
+fn parse_ics(ics: &str) -> ParseOutput {
+    let mut parser = IcsParser::new(None, None).strict(true);
+    parser.parse_ics_chunk(ics).unwrap();
+    sorted(parser.finish().unwrap())
+}
+
+fn parse_jcal(json: &str) -> ParseOutput {
+    let mut parser = IcsParser::new(None, None);
+    parser.parse_jcal(json).unwrap();
+    sorted(parser.finish().unwrap())
+}
+
+fn sorted(mut output: ParseOutput) -> ParseOutput {
+    output
+        .events
+        .sort_by(|a, b| (a.start, &a.uid).cmp(&(b.start, &b.uid)));
+    output
+}
+
+// Event only compares the start
+fn assert_same_events(a: &[Event], b: &[Event]) {
+    assert_eq!(a.len(), b.len());
+    for (a, b) in a.iter().zip(b) {
+        assert_eq!(a.uid, b.uid);
+        assert_eq!(a.summary, b.summary);
+        assert_eq!(
+            (a.start, a.end, a.recurrence_id),
+            (b.start, b.end, b.recurrence_id)
+        );
+        assert_eq!(a.description, b.description);
+        assert_eq!(a.categories, b.categories);
+        assert_eq!((a.status, a.transparency), (b.status, b.transparency));
+        assert_eq!(a.attendees, b.attendees);
+        assert_eq!(a.sequence, b.sequence);
+        assert_eq!(a.x_properties, b.x_properties);
+        assert_eq!(a.alarms, b.alarms);
+    }
+}
+
+#[test]
+fn same_events_as_the_text_form() {
+    let text = parse_ics(ICS);
+    let json = parse_jcal(JCAL);
+
+    assert!(json.diagnostics.is_empty(), "{:?}", json.diagnostics);
+    assert_eq!(json.calendar, text.calendar);
+    assert_same_events(&json.events, &text.events);
+    assert_eq!(json.todos, text.todos);
+
+    // Monday 2 and 9, Wednesday 11, the 4th is excluded
+    let events = &json.events;
+    assert_eq!(events.len(), 4);
+    assert_eq!(
+        events[0].start,
+        EventTime::from(Utc.with_ymd_and_hms(2024, 12, 2, 1, 30, 0).unwrap())
+    );
+    assert_eq!(
+        events[2].start,
+        EventTime::from(Utc.with_ymd_and_hms(2024, 12, 11, 1, 30, 0).unwrap())
+    );
+    assert_eq!(events[0].summary, "Standup; daily, short");
+    assert_eq!(events[0].description.as_deref(), Some("Line one\nLine two"));
+    assert_eq!(events[0].categories, ["Work", "Team, all"]);
+    assert_eq!(events[0].sequence, 2);
+    assert_eq!(
+        events[0].attendees[0].common_name.as_deref(),
+        Some("Doe, John")
+    );
+    assert_eq!(
+        events[0].attendees[0].participation_status,
+        ParticipationStatus::Declined
+    );
+    assert_eq!(events[0].alarms.len(), 2);
+    assert_eq!(
+        events[3].start,
+        EventTime::Date(NaiveDate::from_ymd_opt(2024, 12, 25).unwrap())
+    );
+    assert_eq!(json.calendar.name.as_deref(), Some("Team, shared"));
+}
+
+#[test]
+fn several_calendars_in_one_document() {
+    let json = format!(
+        "[{}, {}]",
+        JCAL,
+        JCAL.replace("@example.com", "@example.org")
+    );
+    let output = parse_jcal(&json);
+    assert!(output.diagnostics.is_empty(), "{:?}", output.diagnostics);
+    assert_eq!(output.events.len(), 8);
+    assert_eq!(output.todos.len(), 2);
+}
+
+#[test]
+fn malformed_properties_are_reported() {
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_jcal(
+            r#"["vcalendar", [], [
+                ["vevent", [
+                    ["summary", {}, "text", "Kept"],
+                    ["dtstart", {}, "date-time", "2024-12-05T10:00:00Z"],
+                    ["location", "Room 3"]
+                ], []],
+                ["vevent", [
+                    ["summary", {}, "text", "Skipped"],
+                    ["dtstart", {}, "date-time", "soon"]
+                ], []],
+                ["vevent"]
+            ]]"#,
+        )
+        .unwrap();
+    let output = parser.finish().unwrap();
+
+    assert_eq!(output.events.len(), 1);
+    assert_eq!(output.events[0].summary, "Kept");
+    assert_eq!(output.skipped_events, 1);
+    let kinds = output
+        .diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.property.as_str(), &diagnostic.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            ("LOCATION", &DiagnosticKind::MalformedLine),
+            ("DTSTART", &DiagnosticKind::InvalidValue("soon".to_string())),
+            ("BEGIN", &DiagnosticKind::MalformedLine),
+        ]
+    );
+    // The line of the property in the text form
+    assert_eq!(output.diagnostics[0].line, 5);
+}
+
+#[test]
+fn invalid_json_fails() {
+    let mut parser = IcsParser::new(None, None);
+    let error = parser.parse_jcal("[\"vcalendar\", [],").unwrap_err();
+    assert!(matches!(
+        error.diagnostic.kind,
+        DiagnosticKind::InvalidJson(_)
+    ));
+    assert_eq!(parser.diagnostics().len(), 1);
+
+    // Also a text feed is not jCal
+    let mut parser = IcsParser::new(None, None);
+    assert!(parser.parse_jcal(ICS).is_err());
+}
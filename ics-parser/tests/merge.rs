@@ -0,0 +1,137 @@
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::parse;
+use ics_parser::EventMerger;
+
+const TEAM: &str = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+UID:planning@example.com\r\n\
+SEQUENCE:1\r\n\
+SUMMARY:Planning\r\n\
+DTSTART:20241205T060000Z\r\n\
+DTEND:20241205T070000Z\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup@example.com\r\n\
+SUMMARY:Stand-up\r\n\
+DTSTART:20241202T013000Z\r\n\
+DTEND:20241202T014500Z\r\n\
+RRULE:FREQ=DAILY;COUNT=3\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:No UID\r\n\
+DTSTART:20241206T060000Z\r\n\
+DTEND:20241206T070000Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+const PERSONAL: &str = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+UID:planning@example.com\r\n\
+SEQUENCE:2\r\n\
+SUMMARY:Planning (moved)\r\n\
+DTSTART:20241205T080000Z\r\n\
+DTEND:20241205T090000Z\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup@example.com\r\n\
+SUMMARY:Stand-up\r\n\
+DTSTART:20241202T013000Z\r\n\
+DTEND:20241202T014500Z\r\n\
+RRULE:FREQ=DAILY;COUNT=3\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup@example.com\r\n\
+RECURRENCE-ID:20241203T013000Z\r\n\
+SEQUENCE:1\r\n\
+SUMMARY:Stand-up + demo\r\n\
+DTSTART:20241203T013000Z\r\n\
+DTEND:20241203T020000Z\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:No UID\r\n\
+DTSTART:20241206T060000Z\r\n\
+DTEND:20241206T070000Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+#[test]
+fn merges_events_shared_by_calendars() {
+    let mut merger = EventMerger::new();
+    merger.add("https://example.com/team.ics", parse(TEAM));
+    merger.add("https://example.com/personal.ics", parse(PERSONAL));
+    let mut merged = merger.finish();
+    merged.sort_by_key(|merged| merged.event.start);
+
+    let summaries = merged
+        .iter()
+        .map(|merged| (merged.event.summary.as_str(), merged.sources.len()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summaries,
+        vec![
+            ("Stand-up", 2),
+            ("Stand-up + demo", 2),
+            ("Stand-up", 2),
+            ("Planning (moved)", 2),
+            ("No UID", 1),
+            ("No UID", 1),
+        ]
+    );
+    assert_eq!(
+        merged[0].sources,
+        vec![
+            "https://example.com/team.ics",
+            "https://example.com/personal.ics"
+        ]
+    );
+}
+
+#[test]
+fn recurrence_ids_of_instances() {
+    let events = parse(PERSONAL);
+    let mut demo = events
+        .iter()
+        .filter(|event| event.uid == "standup@example.com")
+        .map(|event| event.recurrence_id)
+        .collect::<Vec<_>>();
+    demo.sort();
+    let instance = |d| Some(Utc.with_ymd_and_hms(2024, 12, d, 1, 30, 0).unwrap().into());
+    assert_eq!(demo, vec![instance(2), instance(3), instance(4)]);
+}
+
+#[test]
+fn later_last_modified_wins_on_the_same_sequence() {
+    let event = |summary: &str, last_modified: &str| {
+        parse(&format!(
+            "BEGIN:VEVENT\r\nUID:a@example.com\r\nSUMMARY:{}\r\nLAST-MODIFIED:{}\r\n\
+             DTSTART:20241205T060000Z\r\nDTEND:20241205T070000Z\r\nEND:VEVENT\r\n",
+            summary, last_modified
+        ))
+    };
+
+    let mut merger = EventMerger::new();
+    merger.add("a", event("Newer", "20241202T000000Z"));
+    merger.add("b", event("Older", "20241201T000000Z"));
+    merger.add("a", event("Newer again", "20241202T000000Z"));
+    let merged = merger.finish();
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].event.summary, "Newer");
+    assert_eq!(merged[0].sources, vec!["a", "b"]);
+}
+
+#[test]
+fn source_of_the_newer_copy_is_kept() {
+    let mut merger = EventMerger::new();
+    merger.add("team", parse(TEAM));
+    merger.add("personal", parse(PERSONAL));
+    let merged = merger.finish();
+    let planning = merged
+        .iter()
+        .find(|merged| merged.event.uid == "planning@example.com")
+        .unwrap();
+    assert_eq!(planning.event.summary, "Planning (moved)");
+    assert_eq!(planning.source, "personal");
+    assert_eq!(planning.sources, vec!["team", "personal"]);
+}
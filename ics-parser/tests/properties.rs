@@ -0,0 +1,108 @@
+use chrono::{TimeZone, Utc};
+use ics_parser::{Classification, EventStatus, IcsParser, Transparency};
+
+#[test]
+fn moodle_event_properties() {
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_ics_chunk(include_str!("data/moodle.ics"))
+        .unwrap();
+    let events = parser.get_events();
+    let event = &events[0];
+    assert_eq!(event.uid, "1729789@moodle2.ntust.edu.tw");
+    assert_eq!(
+        event.description.as_deref(),
+        Some("請將所有檔案壓縮成一個壓縮檔(.7z或.zip)，檔名: 學號，e.g. B11115888\n\n")
+    );
+    assert_eq!(
+        event.categories,
+        vec!["[TaiwanTech] 程式語言 Programming Languages (1131CS4001301)"]
+    );
+    assert_eq!(event.class, Some(Classification::Public));
+    assert_eq!(
+        event.last_modified,
+        Some(Utc.with_ymd_and_hms(2024, 12, 2, 8, 17, 10).unwrap())
+    );
+    assert_eq!(event.status, None);
+    assert_eq!(event.sequence, 0);
+}
+
+#[test]
+fn all_properties() {
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_ics_chunk(
+            "BEGIN:VCALENDAR\r\n\
+         X-WR-CALNAME:Team\r\n\
+         BEGIN:VEVENT\r\n\
+         UID:retro@example.com\r\n\
+         SUMMARY:Retro\r\n\
+         DESCRIPTION;ALTREP=\"cid:part1\":Bring\\, notes\\nand ideas\r\n\
+         LOCATION;LANGUAGE=en:Room 3\\; 2F\r\n\
+         CATEGORIES:Work,Team\\, core\r\n\
+         CATEGORIES:Meeting\r\n\
+         STATUS:TENTATIVE\r\n\
+         TRANSP:TRANSPARENT\r\n\
+         CLASS:X-INTERNAL\r\n\
+         URL:https://example.com/retro?week=49\r\n\
+         ORGANIZER;CN=Alice:mailto:alice@example.com\r\n\
+         SEQUENCE:3\r\n\
+         LAST-MODIFIED:20241201T093000Z\r\n\
+         X-MICROSOFT-CDO-BUSYSTATUS:FREE\r\n\
+         X-APPLE-TRAVEL-ADVISORY-BEHAVIOR;X-FLAG=1:AUTOMATIC\r\n\
+         DTSTART:20241205T060000Z\r\n\
+         DTEND:20241205T070000Z\r\n\
+         END:VEVENT\r\n\
+         END:VCALENDAR\r\n",
+        )
+        .unwrap();
+    let events = parser.get_events();
+    let event = &events[0];
+    assert_eq!(event.uid, "retro@example.com");
+    assert_eq!(
+        event.description.as_deref(),
+        Some("Bring, notes\nand ideas")
+    );
+    assert_eq!(event.location.as_deref(), Some("Room 3; 2F"));
+    assert_eq!(event.categories, vec!["Work", "Team, core", "Meeting"]);
+    assert_eq!(event.status, Some(EventStatus::Tentative));
+    assert_eq!(event.transparency, Some(Transparency::Transparent));
+    assert_eq!(
+        event.class,
+        Some(Classification::Other("X-INTERNAL".to_string()))
+    );
+    assert_eq!(
+        event.url.as_deref(),
+        Some("https://example.com/retro?week=49")
+    );
+    assert_eq!(event.organizer.as_deref(), Some("mailto:alice@example.com"));
+    assert_eq!(event.sequence, 3);
+    assert_eq!(
+        event.last_modified,
+        Some(Utc.with_ymd_and_hms(2024, 12, 1, 9, 30, 0).unwrap())
+    );
+    assert_eq!(event.x_properties.len(), 2);
+    assert_eq!(event.x_properties["X-MICROSOFT-CDO-BUSYSTATUS"], "FREE");
+    assert_eq!(
+        event.x_properties["X-APPLE-TRAVEL-ADVISORY-BEHAVIOR"],
+        "AUTOMATIC"
+    );
+}
+
+#[test]
+fn recurring_instances_share_properties() {
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_ics_chunk(include_str!("data/google.ics"))
+        .unwrap();
+    let events = parser.get_events();
+    let party = events
+        .iter()
+        .find(|event| event.summary == "Christmas party")
+        .unwrap();
+    assert_eq!(party.transparency, Some(Transparency::Transparent));
+    assert!(events
+        .iter()
+        .filter(|event| event.uid == "0f6bq3pc0ld2kbq5v0a0rj3m4k@google.com")
+        .all(|event| event.status == Some(EventStatus::Confirmed)));
+}
@@ -0,0 +1,226 @@
+mod common;
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use common::{date, event_ics, utc};
+use ics_parser::{EventTime, IcsParser};
+
+fn window(y: i32, m: u32, d: u32) -> Option<DateTime<Utc>> {
+    Some(Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap())
+}
+
+fn parse(
+    properties: &str,
+    start_date: Option<DateTime<Utc>>,
+    end_date: Option<DateTime<Utc>>,
+) -> Vec<EventTime> {
+    let mut parser = IcsParser::new(start_date, end_date);
+    parser.parse_ics_chunk(&event_ics(properties)).unwrap();
+    parser
+        .get_events()
+        .into_iter()
+        .map(|event| event.start)
+        .collect()
+}
+
+fn dates(starts: &[EventTime]) -> Vec<NaiveDate> {
+    starts
+        .iter()
+        .map(|start| start.date(&chrono_tz::UTC))
+        .collect()
+}
+
+#[test]
+fn weekly_by_day_with_count() {
+    let starts = parse(
+        "DTSTART;TZID=Asia/Taipei:20240902T100000
+DTEND;TZID=Asia/Taipei:20240902T120000
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=5",
+        None,
+        None,
+    );
+    assert_eq!(
+        starts,
+        vec![
+            utc(2024, 9, 2, 2, 0),
+            utc(2024, 9, 4, 2, 0),
+            utc(2024, 9, 9, 2, 0),
+            utc(2024, 9, 11, 2, 0),
+            utc(2024, 9, 16, 2, 0),
+        ]
+    );
+}
+
+#[test]
+fn weekly_keeps_local_time_across_dst() {
+    let starts = parse(
+        "DTSTART;TZID=America/New_York:20241028T090000
+DTEND;TZID=America/New_York:20241028T100000
+RRULE:FREQ=WEEKLY;INTERVAL=1;UNTIL=20241111T140000Z",
+        None,
+        None,
+    );
+    assert_eq!(
+        starts,
+        vec![
+            utc(2024, 10, 28, 13, 0),
+            utc(2024, 11, 4, 14, 0),
+            utc(2024, 11, 11, 14, 0),
+        ]
+    );
+}
+
+#[test]
+fn daily_with_interval_and_until() {
+    let starts = parse(
+        "DTSTART:20241201T010000Z
+DTEND:20241201T011500Z
+RRULE:FREQ=DAILY;INTERVAL=3;UNTIL=20241210T010000Z",
+        None,
+        None,
+    );
+    assert_eq!(
+        dates(&starts),
+        vec![
+            date(2024, 12, 1),
+            date(2024, 12, 4),
+            date(2024, 12, 7),
+            date(2024, 12, 10)
+        ]
+    );
+}
+
+#[test]
+fn monthly_by_day_with_ordinals() {
+    let starts = parse(
+        "DTSTART:20240909T010000Z
+DTEND:20240909T020000Z
+RRULE:FREQ=MONTHLY;BYDAY=2MO,-1FR;COUNT=6",
+        None,
+        None,
+    );
+    assert_eq!(
+        dates(&starts),
+        vec![
+            date(2024, 9, 9),
+            date(2024, 9, 27),
+            date(2024, 10, 14),
+            date(2024, 10, 25),
+            date(2024, 11, 11),
+            date(2024, 11, 29),
+        ]
+    );
+}
+
+#[test]
+fn monthly_last_workday_with_by_set_pos() {
+    let starts = parse(
+        "DTSTART:20240830T090000Z
+DTEND:20240830T100000Z
+RRULE:FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1;COUNT=4",
+        None,
+        None,
+    );
+    assert_eq!(
+        dates(&starts),
+        vec![
+            date(2024, 8, 30),
+            date(2024, 9, 30),
+            date(2024, 10, 31),
+            date(2024, 11, 29),
+        ]
+    );
+}
+
+#[test]
+fn monthly_by_month_day_skips_short_months() {
+    let starts = parse(
+        "DTSTART:20250131T090000Z
+DTEND:20250131T100000Z
+RRULE:FREQ=MONTHLY;COUNT=3",
+        None,
+        None,
+    );
+    assert_eq!(
+        dates(&starts),
+        vec![date(2025, 1, 31), date(2025, 3, 31), date(2025, 5, 31)]
+    );
+
+    let starts = parse(
+        "DTSTART:20250131T090000Z
+DTEND:20250131T100000Z
+RRULE:FREQ=MONTHLY;BYMONTHDAY=-1;COUNT=3",
+        None,
+        None,
+    );
+    assert_eq!(
+        dates(&starts),
+        vec![date(2025, 1, 31), date(2025, 2, 28), date(2025, 3, 31)]
+    );
+}
+
+#[test]
+fn yearly_birthday_inside_window() {
+    let starts = parse(
+        "DTSTART;VALUE=DATE:19950517
+DTEND;VALUE=DATE:19950518
+RRULE:FREQ=YEARLY",
+        window(2024, 12, 1),
+        window(2026, 12, 1),
+    );
+    assert_eq!(dates(&starts), vec![date(2025, 5, 17), date(2026, 5, 17)]);
+}
+
+#[test]
+fn yearly_by_month_and_by_day() {
+    // Thanksgiving
+    let starts = parse(
+        "DTSTART;VALUE=DATE:20221124
+DTEND;VALUE=DATE:20221125
+RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=4TH;COUNT=3",
+        None,
+        None,
+    );
+    assert_eq!(
+        dates(&starts),
+        vec![date(2022, 11, 24), date(2023, 11, 23), date(2024, 11, 28)]
+    );
+}
+
+#[test]
+fn endless_rule_stops_at_window_end() {
+    let starts = parse(
+        "DTSTART:20200101T090000Z
+DTEND:20200101T091500Z
+RRULE:FREQ=DAILY",
+        window(2024, 12, 1),
+        window(2024, 12, 8),
+    );
+    assert_eq!(starts.len(), 7);
+    assert_eq!(starts[0], utc(2024, 12, 1, 9, 0));
+}
+
+#[test]
+fn huge_interval_ends_the_rule() {
+    let starts = parse(
+        "DTSTART:20240101T100000Z
+RRULE:FREQ=YEARLY;INTERVAL=400000000",
+        None,
+        None,
+    );
+    assert_eq!(starts, vec![utc(2024, 1, 1, 10, 0)]);
+}
+
+#[test]
+fn rule_starting_long_ago_only_keeps_the_window() {
+    // Over 12,000 daily occurrences happen before the window
+    let starts = parse(
+        "DTSTART:19900101T100000Z
+RRULE:FREQ=DAILY",
+        window(2024, 12, 1),
+        window(2024, 12, 4),
+    );
+    assert_eq!(
+        dates(&starts),
+        vec![date(2024, 12, 1), date(2024, 12, 2), date(2024, 12, 3)]
+    );
+}
@@ -0,0 +1,101 @@
+use std::cell::RefCell;
+
+use ics_parser::{EarliestEvents, Event, EventSink, IcsParser};
+
+const NTUST: &str = include_str!("data/ntust.ics");
+
+#[test]
+fn events_are_pushed_as_soon_as_they_end() {
+    let summaries = RefCell::new(Vec::new());
+    let mut parser = IcsParser::with_sink(None, None, |event: Event| {
+        summaries.borrow_mut().push(event.summary)
+    });
+    parser
+        .parse_ics_chunk(
+            "BEGIN:VCALENDAR\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:First\r\n\
+             DTSTART:20241205T100000Z\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n",
+        )
+        .unwrap();
+    assert_eq!(*summaries.borrow(), vec!["First"]);
+    parser
+        .parse_ics_chunk(
+            "SUMMARY:Second\r\n\
+             DTSTART:20241206T100000Z\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n",
+        )
+        .unwrap();
+    let _ = parser.get_events();
+    assert_eq!(*summaries.borrow(), vec!["First", "Second"]);
+}
+
+struct Counter {
+    pushed: usize,
+}
+
+impl EventSink for Counter {
+    fn push(&mut self, _event: Event) {
+        self.pushed += 1;
+    }
+}
+
+#[test]
+fn custom_sink_sees_events_while_parsing() {
+    let mut parser = IcsParser::with_sink(None, None, Counter { pushed: 0 });
+    let (head, tail) = NTUST.split_at(NTUST.len() / 2);
+    parser.parse_ics_chunk(head).unwrap();
+    let halfway = parser.sink().pushed;
+    assert!(halfway > 0);
+    parser.parse_ics_chunk(tail).unwrap();
+    let output = parser.finish().unwrap();
+    assert!(output.events.pushed > halfway);
+
+    let mut parser = IcsParser::new(None, None);
+    parser.parse_ics_chunk(NTUST).unwrap();
+    assert_eq!(output.events.pushed, parser.get_events().len());
+}
+
+#[test]
+fn earliest_events_keeps_the_first_n() {
+    let mut parser = IcsParser::new(None, None);
+    parser.parse_ics_chunk(NTUST).unwrap();
+    let mut all = parser.get_events();
+    all.sort_by_key(|event| event.start);
+
+    let mut parser = IcsParser::with_sink(None, None, EarliestEvents::new(5));
+    parser.parse_ics_chunk(NTUST).unwrap();
+    let earliest = parser.get_events();
+    assert_eq!(earliest.len(), 5);
+
+    let starts = |events: &[Event]| events.iter().map(|event| event.start).collect::<Vec<_>>();
+    assert_eq!(starts(&earliest.into_sorted_vec()), starts(&all[..5]));
+}
+
+#[test]
+fn earliest_events_includes_recurring_events() {
+    let mut parser = IcsParser::with_sink(None, None, EarliestEvents::new(2));
+    parser
+        .parse_ics_chunk(
+            "BEGIN:VEVENT\r\n\
+             SUMMARY:Late\r\n\
+             DTSTART:20241220T100000Z\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:Daily\r\n\
+             DTSTART:20241201T100000Z\r\n\
+             RRULE:FREQ=DAILY;COUNT=10\r\n\
+             END:VEVENT\r\n",
+        )
+        .unwrap();
+    let events = parser.get_events().into_sorted_vec();
+    let summaries = events
+        .iter()
+        .map(|event| event.summary.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(summaries, vec!["Daily", "Daily"]);
+    assert!(EarliestEvents::new(0).is_empty());
+}
@@ -0,0 +1,210 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use ics_parser::{
+    Alarm, AlarmAction, Event, IcsParser, Snapshot, SnapshotError, Todo, TodoStatus,
+    SNAPSHOT_VERSION,
+};
+
+fn snapshot(ics: &str) -> Snapshot {
+    let mut parser = IcsParser::new(None, None);
+    parser.parse_ics_chunk(ics).unwrap();
+    let output = parser.finish().unwrap();
+    Snapshot {
+        created: Utc.with_ymd_and_hms(2024, 12, 5, 8, 0, 0).unwrap(),
+        events: output.events,
+        todos: output.todos,
+    }
+}
+
+fn example() -> Snapshot {
+    let mut event = Event::new(
+        "Meeting, room 3",
+        Utc.with_ymd_and_hms(2024, 12, 5, 10, 0, 0).unwrap().into(),
+        Utc.with_ymd_and_hms(2024, 12, 5, 11, 0, 0).unwrap().into(),
+    );
+    event.uid = "meeting@example.com".to_string();
+    event.location = Some("會議室".to_string());
+    event.alarms = vec![
+        Alarm {
+            action: AlarmAction::Display,
+            description: None,
+            time: Utc.with_ymd_and_hms(2024, 12, 5, 9, 45, 0).unwrap(),
+        },
+        Alarm {
+            action: AlarmAction::Other("X-PUSH".to_string()),
+            description: None,
+            time: Utc.with_ymd_and_hms(1969, 12, 31, 0, 0, 0).unwrap(),
+        },
+    ];
+    let holiday = Event::new(
+        "Holiday",
+        NaiveDate::from_ymd_opt(2024, 12, 25).unwrap().into(),
+        NaiveDate::from_ymd_opt(2024, 12, 26).unwrap().into(),
+    );
+    let todo = Todo {
+        uid: "report@example.com".to_string(),
+        summary: "Report".to_string(),
+        due: Some(NaiveDate::from_ymd_opt(2024, 12, 20).unwrap().into()),
+        priority: 1,
+        status: Some(TodoStatus::InProcess),
+        ..Todo::default()
+    };
+
+    Snapshot {
+        created: Utc.with_ymd_and_hms(2024, 12, 5, 8, 0, 0).unwrap(),
+        events: vec![event, holiday],
+        todos: vec![todo],
+    }
+}
+
+fn assert_same(a: &Snapshot, b: &Snapshot) {
+    assert_eq!(a.created, b.created);
+    assert_eq!(a.events.len(), b.events.len());
+    for (a, b) in a.events.iter().zip(&b.events) {
+        assert_eq!(a.uid, b.uid);
+        assert_eq!(a.summary, b.summary);
+        assert_eq!(a.location, b.location);
+        assert_eq!((a.start, a.end), (b.start, b.end));
+        assert_eq!(a.alarms, b.alarms);
+    }
+    assert_eq!(a.todos, b.todos);
+}
+
+#[test]
+fn round_trips_events_and_todos() {
+    let original = example();
+    let decoded = Snapshot::decode(&original.encode()).unwrap();
+    assert_same(&decoded, &original);
+    // Only what the device shows is kept
+    assert_eq!(decoded.events[0].description, None);
+}
+
+#[test]
+fn round_trips_fixtures() {
+    for ics in [
+        include_str!("data/ntust.ics"),
+        include_str!("data/google.ics"),
+        include_str!("data/outlook.ics"),
+    ] {
+        let original = snapshot(ics);
+        let bytes = original.encode();
+        assert_same(&Snapshot::decode(&bytes).unwrap(), &original);
+        // Much smaller than the feed it came from
+        assert!(bytes.len() < ics.len() / 2, "{} bytes", bytes.len());
+    }
+}
+
+#[test]
+fn empty_snapshot() {
+    let original = Snapshot {
+        created: Utc.with_ymd_and_hms(2024, 12, 5, 8, 0, 0).unwrap(),
+        events: Vec::new(),
+        todos: Vec::new(),
+    };
+    let bytes = original.encode();
+    assert_eq!(bytes.len(), 4 + 1 + 5 + 1 + 1 + 4);
+    assert_same(&Snapshot::decode(&bytes).unwrap(), &original);
+}
+
+#[test]
+fn rejects_other_data() {
+    assert_eq!(Snapshot::decode(b"").err(), Some(SnapshotError::BadMagic));
+    assert_eq!(
+        Snapshot::decode(b"BEGIN:VCALENDAR\r\n").err(),
+        Some(SnapshotError::BadMagic)
+    );
+    assert_eq!(
+        Snapshot::decode(b"SDCS").err(),
+        Some(SnapshotError::Malformed)
+    );
+
+    let mut bytes = example().encode();
+    bytes[4] = SNAPSHOT_VERSION + 1;
+    assert_eq!(
+        Snapshot::decode(&bytes).err(),
+        Some(SnapshotError::UnsupportedVersion(SNAPSHOT_VERSION + 1))
+    );
+}
+
+#[test]
+fn detects_damaged_data() {
+    let bytes = example().encode();
+
+    for index in 5..bytes.len() {
+        for bit in 0..8 {
+            let mut damaged = bytes.clone();
+            damaged[index] ^= 1 << bit;
+            assert_eq!(
+                Snapshot::decode(&damaged).unwrap_err(),
+                SnapshotError::ChecksumMismatch,
+                "bit {} of byte {}",
+                bit,
+                index
+            );
+        }
+    }
+
+    for len in 5..bytes.len() {
+        assert!(Snapshot::decode(&bytes[..len]).is_err(), "{} bytes", len);
+    }
+}
+
+// Checksums of the input are recomputed, so the payload is what gets tested
+fn with_checksum(mut payload: Vec<u8>) -> Vec<u8> {
+    let mut crc = !0_u32;
+    for &byte in &payload {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    payload.extend_from_slice(&(!crc).to_le_bytes());
+    payload
+}
+
+#[test]
+fn checksum_is_crc32() {
+    let bytes = example().encode();
+    let (payload, _) = bytes.split_at(bytes.len() - 4);
+    assert_eq!(with_checksum(payload.to_vec()), bytes);
+}
+
+#[test]
+fn malformed_payloads_do_not_panic() {
+    let bytes = example().encode();
+    let payload = &bytes[..bytes.len() - 4];
+
+    // Truncated and damaged payloads, with a valid checksum
+    for len in 5..payload.len() {
+        let result = Snapshot::decode(&with_checksum(payload[..len].to_vec()));
+        assert_eq!(result.unwrap_err(), SnapshotError::Malformed);
+    }
+    let mut random = 0x2545_f491_u32;
+    for index in 5..payload.len() {
+        for _ in 0..32 {
+            // xorshift, so the test always runs the same way
+            random ^= random << 13;
+            random ^= random >> 17;
+            random ^= random << 5;
+            let mut damaged = payload.to_vec();
+            damaged[index] = random as u8;
+            let _ = Snapshot::decode(&with_checksum(damaged));
+        }
+    }
+
+    // Counts and lengths larger than the input
+    let mut huge_count = b"SDCS\x01\x00".to_vec();
+    huge_count.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]);
+    assert_eq!(
+        Snapshot::decode(&with_checksum(huge_count)).err(),
+        Some(SnapshotError::Malformed)
+    );
+    let overlong = b"SDCS\x01\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01".to_vec();
+    assert_eq!(
+        Snapshot::decode(&with_checksum(overlong)).err(),
+        Some(SnapshotError::Malformed)
+    );
+}
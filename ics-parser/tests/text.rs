@@ -0,0 +1,46 @@
+use ics_parser::IcsParser;
+
+const MOODLE: &str = include_str!("data/moodle.ics");
+
+#[test]
+fn unescapes_text_values() {
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_ics_chunk(
+            "BEGIN:VEVENT\r\n\
+         SUMMARY:Lunch\\, then a meeting\\; maybe\\nC:\\\\Users\\x\r\n\
+         DTSTART:20241212T120000Z\r\n\
+         DTEND:20241212T130000Z\r\n\
+         END:VEVENT\r\n",
+        )
+        .unwrap();
+    let events = parser.get_events();
+    assert_eq!(
+        events[0].summary,
+        "Lunch, then a meeting; maybe\nC:\\Users\\x"
+    );
+}
+
+#[test]
+fn keeps_html_entities_by_default() {
+    let mut parser = IcsParser::new(None, None);
+    parser.parse_ics_chunk(MOODLE).unwrap();
+    let events = parser.get_events();
+    assert_eq!(
+        events[0].summary,
+        "Programming assignment 2 (Scheme &amp; Prolog)作業繳交截止"
+    );
+}
+
+#[test]
+fn decodes_html_entities_when_enabled() {
+    let mut parser = IcsParser::new(None, None).decode_html_entities(true);
+    parser.parse_ics_chunk(MOODLE).unwrap();
+    parser.parse_ics_chunk("BEGIN:VEVENT\r\nSUMMARY:&lt;b&gt; &#x4F5C;&#26989; &unknown; & done\r\nDTSTART:20241212T120000Z\r\nDTEND:20241212T130000Z\r\nEND:VEVENT\r\n").unwrap();
+    let events = parser.get_events();
+    assert_eq!(
+        events[0].summary,
+        "Programming assignment 2 (Scheme & Prolog)作業繳交截止"
+    );
+    assert_eq!(events[6].summary, "<b> 作業 &unknown; & done");
+}
@@ -0,0 +1,165 @@
+mod common;
+
+use common::utc;
+use ics_parser::{EventTime, IcsParser};
+
+fn parse(ics: &str) -> Vec<(EventTime, EventTime)> {
+    common::parse(ics)
+        .into_iter()
+        .map(|event| (event.start, event.end))
+        .collect()
+}
+
+#[test]
+fn outlook_windows_names_and_custom_zones() {
+    let events = parse(include_str!("data/outlook.ics"));
+    assert_eq!(
+        events,
+        vec![
+            // Customized Time Zone, daylight time
+            (utc(2024, 7, 10, 13, 0), utc(2024, 7, 10, 14, 0)),
+            // Customized Time Zone, across the November transition
+            (utc(2024, 10, 28, 13, 0), utc(2024, 10, 28, 13, 30)),
+            (utc(2024, 11, 4, 14, 0), utc(2024, 11, 4, 14, 30)),
+            (utc(2024, 11, 11, 14, 0), utc(2024, 11, 11, 14, 30)),
+            // Taipei Standard Time
+            (utc(2024, 12, 5, 6, 0), utc(2024, 12, 5, 7, 0)),
+            // Pacific Standard Time, without a VTIMEZONE block
+            (utc(2024, 12, 5, 18, 0), utc(2024, 12, 5, 19, 0)),
+        ]
+    );
+}
+
+#[test]
+fn custom_zone_without_rules() {
+    let events = parse(
+        "BEGIN:VCALENDAR\r\n\
+         BEGIN:VTIMEZONE\r\n\
+         TZID:Office\r\n\
+         BEGIN:STANDARD\r\n\
+         DTSTART:19700101T000000\r\n\
+         TZOFFSETFROM:+0530\r\n\
+         TZOFFSETTO:+0530\r\n\
+         END:STANDARD\r\n\
+         END:VTIMEZONE\r\n\
+         BEGIN:VEVENT\r\n\
+         DTSTART;TZID=Office:20241205T100000\r\n\
+         DTEND;TZID=Office:20241205T110000\r\n\
+         END:VEVENT\r\n\
+         BEGIN:VEVENT\r\n\
+         DTSTART;TZID=Mars Standard Time:20241205T100000\r\n\
+         DTEND;TZID=Mars Standard Time:20241205T110000\r\n\
+         END:VEVENT\r\n\
+         END:VCALENDAR\r\n",
+    );
+    assert_eq!(
+        events,
+        vec![(utc(2024, 12, 5, 4, 30), utc(2024, 12, 5, 5, 30))]
+    );
+}
+
+#[test]
+fn rule_matching_no_day_is_ignored() {
+    let mut ics = String::from(
+        "BEGIN:VCALENDAR\r\n\
+         BEGIN:VTIMEZONE\r\n\
+         TZID:Office\r\n\
+         BEGIN:STANDARD\r\n\
+         DTSTART:19701025T030000\r\n\
+         TZOFFSETFROM:+0200\r\n\
+         TZOFFSETTO:+0100\r\n\
+         RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU\r\n\
+         END:STANDARD\r\n\
+         BEGIN:DAYLIGHT\r\n\
+         DTSTART:19700330T020000\r\n\
+         TZOFFSETFROM:+0100\r\n\
+         TZOFFSETTO:+0200\r\n\
+         RRULE:FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30\r\n\
+         END:DAYLIGHT\r\n\
+         END:VTIMEZONE\r\n",
+    );
+    // Searching the rule on every conversion took seconds for a feed like this
+    for day in 1..=28 {
+        ics.push_str(&format!(
+            "BEGIN:VEVENT\r\n\
+             DTSTART;TZID=Office:202407{:02}T100000\r\n\
+             DTEND;TZID=Office:202407{:02}T110000\r\n\
+             END:VEVENT\r\n",
+            day, day
+        ));
+    }
+    ics.push_str("END:VCALENDAR\r\n");
+
+    let started = std::time::Instant::now();
+    let events = parse(&ics);
+    assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    // Daylight time only began in 1970, standard time applies since October 2023
+    assert_eq!(events.len(), 28);
+    assert_eq!(events[0], (utc(2024, 7, 1, 9, 0), utc(2024, 7, 1, 10, 0)));
+}
+
+#[test]
+fn vtimezone_does_not_leak_into_events() {
+    let events = parse(include_str!("data/google.ics"));
+    assert_eq!(events.len(), 10);
+}
+
+const FLOATING: &str = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART:20241205T100000\r\n\
+DTEND:20241205T110000\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+#[test]
+fn floating_times_default_to_utc() {
+    let mut parser = IcsParser::new(None, None);
+    parser.parse_ics_chunk(FLOATING).unwrap();
+    let events = parser.get_events();
+    assert_eq!(events[0].start, utc(2024, 12, 5, 10, 0));
+}
+
+#[test]
+fn floating_times_use_default_timezone() {
+    let mut parser = IcsParser::new(None, None).default_timezone(chrono_tz::Asia::Taipei);
+    parser.parse_ics_chunk(FLOATING).unwrap();
+    let events = parser.get_events();
+    assert_eq!(events[0].start, utc(2024, 12, 5, 2, 0));
+    assert_eq!(events[0].end, utc(2024, 12, 5, 3, 0));
+}
+
+#[test]
+fn floating_times_prefer_calendar_timezone() {
+    let mut parser = IcsParser::new(None, None).default_timezone(chrono_tz::Asia::Taipei);
+    parser
+        .parse_ics_chunk(&FLOATING.replace(
+            "BEGIN:VCALENDAR\r\n",
+            "BEGIN:VCALENDAR\r\nX-WR-TIMEZONE:Europe/Berlin\r\n",
+        ))
+        .unwrap();
+    let events = parser.get_events();
+    assert_eq!(events[0].start, utc(2024, 12, 5, 9, 0));
+}
+
+#[test]
+fn floating_recurrence_keeps_local_time_across_dst() {
+    let mut parser = IcsParser::new(None, None).default_timezone(chrono_tz::Europe::Berlin);
+    parser
+        .parse_ics_chunk(
+            "BEGIN:VEVENT\r\n\
+         DTSTART:20241021T090000\r\n\
+         DTEND:20241021T100000\r\n\
+         RRULE:FREQ=WEEKLY;COUNT=2\r\n\
+         EXDATE:20241028T090000\r\n\
+         RDATE:20241104T090000\r\n\
+         END:VEVENT\r\n",
+        )
+        .unwrap();
+    let mut events = parser.get_events();
+    events.sort_by_key(|event| event.start);
+    let starts = events.iter().map(|event| event.start).collect::<Vec<_>>();
+    assert_eq!(
+        starts,
+        vec![utc(2024, 10, 21, 7, 0), utc(2024, 11, 4, 8, 0)]
+    );
+}
@@ -0,0 +1,146 @@
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use ics_parser::{DiagnosticKind, EventTime, IcsParser, Todo, TodoStatus};
+
+const TASKS: &str = "BEGIN:VCALENDAR\r\n\
+BEGIN:VTODO\r\n\
+UID:report@example.com\r\n\
+SUMMARY:Hand in report\r\n\
+DTSTART:20241201T090000Z\r\n\
+DUE:20241210T170000Z\r\n\
+PRIORITY:1\r\n\
+PERCENT-COMPLETE:40\r\n\
+STATUS:IN-PROCESS\r\n\
+BEGIN:VALARM\r\n\
+ACTION:DISPLAY\r\n\
+DESCRIPTION:Report due\r\n\
+TRIGGER:-PT1H\r\n\
+END:VALARM\r\n\
+END:VTODO\r\n\
+BEGIN:VTODO\r\n\
+SUMMARY:Buy milk\r\n\
+DUE;VALUE=DATE:20241205\r\n\
+COMPLETED:20241204T183000Z\r\n\
+STATUS:COMPLETED\r\n\
+END:VTODO\r\n\
+BEGIN:VTODO\r\n\
+SUMMARY:Someday\r\n\
+END:VTODO\r\n\
+BEGIN:VTODO\r\n\
+SUMMARY:Next year\r\n\
+DUE;VALUE=DATE:20250601\r\n\
+END:VTODO\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Meeting\r\n\
+DTSTART:20241205T100000Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+fn utc(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+}
+
+fn parse(ics: &str, end_date: Option<DateTime<Utc>>) -> Vec<Todo> {
+    let mut parser = IcsParser::new(None, end_date);
+    parser.parse_ics_chunk(ics).unwrap();
+    let output = parser.finish().unwrap();
+    assert_eq!(output.events.len(), 1);
+    output.todos
+}
+
+#[test]
+fn parses_task_properties() {
+    let todos = parse(TASKS, None);
+    assert_eq!(todos.len(), 4);
+
+    let report = &todos[0];
+    assert_eq!(report.uid, "report@example.com");
+    assert_eq!(report.summary, "Hand in report");
+    assert_eq!(report.description, None);
+    assert_eq!(report.start, Some(EventTime::from(utc(2024, 12, 1, 9))));
+    assert_eq!(report.due, Some(EventTime::from(utc(2024, 12, 10, 17))));
+    assert_eq!(report.priority, 1);
+    assert_eq!(report.percent_complete, Some(40));
+    assert_eq!(report.status, Some(TodoStatus::InProcess));
+    assert!(report.is_open());
+
+    let milk = &todos[1];
+    assert_eq!(
+        milk.due,
+        Some(EventTime::from(
+            NaiveDate::from_ymd_opt(2024, 12, 5).unwrap()
+        ))
+    );
+    assert_eq!(
+        milk.completed,
+        Some(Utc.with_ymd_and_hms(2024, 12, 4, 18, 30, 0).unwrap())
+    );
+    assert!(milk.is_completed());
+    assert!(!milk.is_open());
+
+    assert_eq!(todos[2].due, None);
+    assert_eq!(todos[2].priority, 0);
+    assert!(todos[2].is_open());
+}
+
+#[test]
+fn any_completion_marker_closes_the_task() {
+    let done = Todo {
+        percent_complete: Some(100),
+        ..Todo::default()
+    };
+    assert!(done.is_completed());
+
+    let cancelled = Todo {
+        status: Some(TodoStatus::Cancelled),
+        ..Todo::default()
+    };
+    assert!(!cancelled.is_completed());
+    assert!(!cancelled.is_open());
+}
+
+#[test]
+fn tasks_after_the_window_are_left_out() {
+    let summaries = parse(TASKS, Some(utc(2025, 1, 1, 0)))
+        .into_iter()
+        .map(|todo| todo.summary)
+        .collect::<Vec<_>>();
+    assert_eq!(summaries, vec!["Hand in report", "Buy milk", "Someday"]);
+}
+
+#[test]
+fn tasks_due_before_the_window_are_left_out() {
+    let mut parser = IcsParser::new(Some(utc(2024, 12, 6, 0)), None);
+    parser.parse_ics_chunk(TASKS).unwrap();
+    let summaries = parser
+        .finish()
+        .unwrap()
+        .todos
+        .into_iter()
+        .map(|todo| todo.summary)
+        .collect::<Vec<_>>();
+    assert_eq!(summaries, vec!["Hand in report", "Someday", "Next year"]);
+}
+
+#[test]
+fn invalid_tasks_are_dropped() {
+    let mut parser = IcsParser::new(None, None);
+    parser
+        .parse_ics_chunk(
+            "BEGIN:VTODO\r\n\
+             SUMMARY:Broken\r\n\
+             PERCENT-COMPLETE:150\r\n\
+             DUE:20241210T170000Z\r\n\
+             END:VTODO\r\n\
+             BEGIN:VTODO\r\n\
+             SUMMARY:Fine\r\n\
+             END:VTODO\r\n",
+        )
+        .unwrap();
+    let output = parser.finish().unwrap();
+    assert_eq!(output.todos.len(), 1);
+    assert_eq!(output.todos[0].summary, "Fine");
+    assert_eq!(
+        output.diagnostics[0].kind,
+        DiagnosticKind::InvalidValue("150".to_string())
+    );
+}
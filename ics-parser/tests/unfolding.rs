@@ -0,0 +1,49 @@
+use ics_parser::IcsParser;
+
+const FOLDED: &str = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Introduction to Data Science HW2 作業繳交截止 and a very long tit\r\n le that keeps going\r\n\
+\tuntil it is folded twice\r\n\
+DTSTART:20241212T155900Z\r\n\
+DTEND:20241212T165900Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+const EXPECTED: &str = "Introduction to Data Science HW2 作業繳交截止 and a very long title that keeps goinguntil it is folded twice";
+
+fn parse_chunks(chunks: &[&str]) -> Vec<ics_parser::Event> {
+    let mut parser = IcsParser::new(None, None);
+    for chunk in chunks {
+        parser.parse_ics_chunk(chunk).unwrap();
+    }
+    parser.get_events()
+}
+
+#[test]
+fn unfolds_folded_summary() {
+    let events = parse_chunks(&[FOLDED]);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].summary, EXPECTED);
+}
+
+#[test]
+fn unfolds_across_every_chunk_boundary() {
+    for (split, _) in FOLDED.char_indices().skip(1) {
+        let events = parse_chunks(&[&FOLDED[..split], &FOLDED[split..]]);
+        assert_eq!(events.len(), 1, "split at byte {}", split);
+        assert_eq!(events[0].summary, EXPECTED, "split at byte {}", split);
+    }
+}
+
+#[test]
+fn parses_input_without_trailing_newline() {
+    let events = parse_chunks(&[FOLDED.trim_end_matches("\r\nEND:VCALENDAR\r\n")]);
+    assert_eq!(events.len(), 1);
+}
+
+#[test]
+fn parses_moodle_feed_with_folded_lines() {
+    let events = parse_chunks(&[include_str!("data/moodle.ics")]);
+    assert_eq!(events.len(), 6);
+    assert_eq!(events[5].summary, "Final project 書面報告作業繳交截止");
+}
@@ -0,0 +1,105 @@
+use chrono::{DateTime, TimeZone, Utc};
+use ics_parser::IcsParser;
+
+fn utc(y: i32, m: u32, d: u32) -> Option<DateTime<Utc>> {
+    Some(Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap())
+}
+
+const EVENTS: &str = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Conference\r\n\
+DTSTART:20241202T010000Z\r\n\
+DTEND:20241213T090000Z\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Semester\r\n\
+DTSTART;VALUE=DATE:20240902\r\n\
+DTEND;VALUE=DATE:20250112\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Meeting\r\n\
+DTSTART:20241209T020000Z\r\n\
+DTEND:20241209T030000Z\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Last week\r\n\
+DTSTART:20241202T020000Z\r\n\
+DTEND:20241202T030000Z\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Next month\r\n\
+DTSTART;VALUE=DATE:20250105\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Deadline\r\n\
+DTSTART:20241208T000000Z\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+fn summaries(start_date: Option<DateTime<Utc>>, end_date: Option<DateTime<Utc>>) -> Vec<String> {
+    let mut parser = IcsParser::new(start_date, end_date);
+    parser.parse_ics_chunk(EVENTS).unwrap();
+    let mut events = parser.get_events();
+    events.sort_by_key(|event| event.start);
+    events.into_iter().map(|event| event.summary).collect()
+}
+
+#[test]
+fn keeps_events_overlapping_the_window() {
+    assert_eq!(
+        summaries(utc(2024, 12, 8), utc(2024, 12, 15)),
+        vec!["Semester", "Conference", "Deadline", "Meeting"]
+    );
+}
+
+#[test]
+fn window_bounds_are_exclusive_for_touching_events() {
+    // The meeting ends when the window starts, the window ends when the meeting starts
+    let start = Utc.with_ymd_and_hms(2024, 12, 2, 3, 0, 0).single();
+    let end = Utc.with_ymd_and_hms(2024, 12, 9, 2, 0, 0).single();
+    assert_eq!(
+        summaries(start, end),
+        vec!["Semester", "Conference", "Deadline"]
+    );
+}
+
+#[test]
+fn open_ended_windows() {
+    assert_eq!(
+        summaries(utc(2024, 12, 10), None),
+        vec!["Semester", "Conference", "Next month"]
+    );
+    assert_eq!(
+        summaries(None, utc(2024, 12, 3)),
+        vec!["Semester", "Conference", "Last week"]
+    );
+    assert_eq!(summaries(None, None).len(), 6);
+}
+
+#[test]
+fn recurring_events_overlapping_the_window_start() {
+    let mut parser = IcsParser::new(
+        Some(Utc.with_ymd_and_hms(2024, 12, 3, 12, 0, 0).unwrap()),
+        None,
+    );
+    parser
+        .parse_ics_chunk(
+            "BEGIN:VEVENT\r\n\
+         SUMMARY:Night shift\r\n\
+         DTSTART:20241201T200000Z\r\n\
+         DTEND:20241202T200000Z\r\n\
+         RRULE:FREQ=DAILY;COUNT=3\r\n\
+         END:VEVENT\r\n",
+        )
+        .unwrap();
+    let mut events = parser.get_events();
+    events.sort_by_key(|event| event.start);
+    let starts = events.iter().map(|event| event.start).collect::<Vec<_>>();
+    assert_eq!(
+        starts,
+        vec![
+            Utc.with_ymd_and_hms(2024, 12, 2, 20, 0, 0).unwrap().into(),
+            Utc.with_ymd_and_hms(2024, 12, 3, 20, 0, 0).unwrap().into(),
+        ]
+    );
+}
@@ -0,0 +1,135 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use ics_parser::{
+    to_ics_string, Attendee, CalendarInfo, Event, EventStatus, EventTime, IcsParser, IcsWriter,
+    ParseOutput, ParticipationStatus,
+};
+
+fn parse(ics: &str) -> ParseOutput {
+    let mut parser = IcsParser::new(None, None).strict(true);
+    parser.parse_ics_chunk(ics).unwrap();
+    let mut output = parser.finish().unwrap();
+    output
+        .events
+        .sort_by(|a, b| (a.start, &a.uid).cmp(&(b.start, &b.uid)));
+    output
+}
+
+// Event only compares the start
+fn assert_same_events(a: &[Event], b: &[Event]) {
+    assert_eq!(a.len(), b.len());
+    for (a, b) in a.iter().zip(b) {
+        assert_eq!(a.uid, b.uid);
+        assert_eq!(a.summary, b.summary);
+        assert_eq!(
+            (a.start, a.end, a.recurrence_id),
+            (b.start, b.end, b.recurrence_id)
+        );
+        assert_eq!(a.description, b.description);
+        assert_eq!(a.location, b.location);
+        assert_eq!(a.categories, b.categories);
+        assert_eq!((a.status, a.transparency), (b.status, b.transparency));
+        assert_eq!(a.class, b.class);
+        assert_eq!((&a.url, &a.organizer), (&b.url, &b.organizer));
+        assert_eq!(a.attendees, b.attendees);
+        assert_eq!((a.sequence, a.last_modified), (b.sequence, b.last_modified));
+        assert_eq!(a.x_properties, b.x_properties);
+        assert_eq!(a.alarms, b.alarms);
+    }
+}
+
+fn round_trip(ics: &str) {
+    let original = parse(ics);
+    let written = to_ics_string(&original.calendar, &original.events);
+    let read_back = parse(&written);
+
+    assert_eq!(read_back.calendar, original.calendar);
+    assert_same_events(&read_back.events, &original.events);
+    // Writing again gives the same text
+    assert_eq!(
+        to_ics_string(&read_back.calendar, &read_back.events),
+        written
+    );
+}
+
+#[test]
+fn round_trips_ntust() {
+    round_trip(include_str!("data/ntust.ics"));
+}
+
+#[test]
+fn round_trips_moodle() {
+    round_trip(include_str!("data/moodle.ics"));
+}
+
+#[test]
+fn round_trips_recurring_events() {
+    round_trip(include_str!("data/google.ics"));
+}
+
+#[test]
+fn lines_are_folded_at_75_octets() {
+    let mut event = Event::new(
+        &"週會，討論專題進度與下週安排".repeat(4),
+        Utc.with_ymd_and_hms(2024, 12, 5, 10, 0, 0).unwrap().into(),
+        Utc.with_ymd_and_hms(2024, 12, 5, 11, 0, 0).unwrap().into(),
+    );
+    event.description = Some("a".repeat(200));
+    let ics = to_ics_string(&CalendarInfo::default(), &[event.clone()]);
+
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    for line in ics.split_terminator("\r\n") {
+        assert!(line.len() <= 75, "{:?} is {} octets", line, line.len());
+        assert!(!line.contains('\n'));
+    }
+    let events = parse(&ics).events;
+    assert_eq!(events[0].summary, event.summary);
+    assert_eq!(events[0].description, event.description);
+}
+
+#[test]
+fn text_and_parameters_are_escaped() {
+    let mut event = Event::new(
+        "Review; part 1, 2 \\ notes",
+        NaiveDate::from_ymd_opt(2024, 12, 5).unwrap().into(),
+        NaiveDate::from_ymd_opt(2024, 12, 6).unwrap().into(),
+    );
+    event.description = Some("Line one\nLine two".to_string());
+    event.categories = vec!["A, B".to_string(), "C".to_string()];
+    event.status = Some(EventStatus::Tentative);
+    event.attendees.push(Attendee {
+        address: "mailto:doe@example.com".to_string(),
+        common_name: Some("Doe, \"JD\": John".to_string()),
+        participation_status: ParticipationStatus::Accepted,
+    });
+    let calendar = CalendarInfo {
+        name: Some("Team; shared".to_string()),
+        ..CalendarInfo::default()
+    };
+
+    let mut writer = IcsWriter::new(Vec::new(), &calendar).unwrap();
+    writer.write_event(&event).unwrap();
+    let ics = String::from_utf8(writer.finish().unwrap()).unwrap();
+
+    assert!(ics.contains("\r\nX-WR-CALNAME:Team\\; shared\r\n"));
+    assert!(ics.contains("\r\nDTSTART;VALUE=DATE:20241205\r\n"));
+    assert!(ics.contains("\r\nSUMMARY:Review\\; part 1\\, 2 \\\\ notes\r\n"));
+    assert!(ics.contains("\r\nDESCRIPTION:Line one\\nLine two\r\n"));
+    assert!(ics.contains("\r\nCATEGORIES:A\\, B,C\r\n"));
+    assert!(ics.contains(
+        "\r\nATTENDEE;CN=\"Doe, 'JD': John\";PARTSTAT=ACCEPTED:mailto:doe@example.com\r\n"
+    ));
+
+    let output = parse(&ics);
+    assert_eq!(output.calendar.name, calendar.name);
+    let read_back = &output.events[0];
+    assert_eq!(read_back.summary, event.summary);
+    assert_eq!(
+        read_back.start,
+        EventTime::Date(NaiveDate::from_ymd_opt(2024, 12, 5).unwrap())
+    );
+    assert_eq!(read_back.categories, event.categories);
+    assert_eq!(
+        read_back.attendees[0].common_name.as_deref(),
+        Some("Doe, 'JD': John")
+    );
+}